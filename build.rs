@@ -0,0 +1,17 @@
+use std::process::Command;
+
+/*
+*   Record the git revision of the build, so that benchmark results can be traced back to the code
+*/
+fn main() {
+    let revision = Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"));
+    println!("cargo:rustc-env=GIT_REVISION={}", revision);
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/index");
+}
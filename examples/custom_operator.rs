@@ -0,0 +1,37 @@
+/*
+*   Register an operator of your own and run the command line with it, e.g.
+*   cargo run --release --example custom_operator -- solve DSJC125.5 --local recolor_random
+*/
+use discrete_fpa::registry::*;
+use discrete_fpa::structs::*;
+use discrete_fpa::levy::*;
+use rand::Rng;
+
+/*
+*   Recolor a random conflicting node with a random color. Every flower has its own operator,
+*   so the buffer of conflicting nodes is reused between generations.
+*/
+#[derive(Default)]
+struct RecolorRandom {
+    critical: Vec<usize>
+}
+
+impl LocalOperator for RecolorRandom {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, x: &Coloring, offspring: &mut Coloring, k: usize, _levy: &LevyFlight) {
+        self.critical.clear();
+        self.critical.extend((0..x.len()).filter(|&i| x.conflicts[i] != 0));
+        if !self.critical.is_empty() {
+            let i = self.critical[rng.gen_range(0..self.critical.len())];
+            offspring[i] = rng.gen_range(1..=k);
+            offspring.update_conflicts(i, x, g);
+        }
+    }
+}
+
+fn main() {
+    register_local("recolor_random", || Box::new(RecolorRandom::default()));
+    register_pollinator("LR", "levy_pop1", "recolor_random");
+
+    let args: Vec<String> = std::env::args().collect();
+    discrete_fpa::run(&args);
+}
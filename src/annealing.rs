@@ -0,0 +1,225 @@
+use super::structs::*;
+use super::*;
+
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+
+/*
+*   How the temperature of simulated annealing develops
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cooling {
+    // Multiply the temperature by alpha after every temperature level
+    Geometric,
+    // Geometric, but when the best coloring did not improve for patience levels the temperature
+    // goes back up to reheat times the temperature the best coloring was found at
+    Reheating
+}
+
+impl FromStr for Cooling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Cooling, String> {
+        match s {
+            "geometric" => Ok(Cooling::Geometric),
+            "reheating" => Ok(Cooling::Reheating),
+            _ => Err(format!("Unknown cooling schedule {}, use geometric or reheating", s))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnealingParameters {
+    pub initial_temperature: f64,
+    pub min_temperature: f64,
+    pub alpha: f64,
+    // Moves per temperature level, 0 means n * k
+    pub moves: usize,
+    pub patience: usize,
+    pub reheat: f64
+}
+
+impl AnnealingParameters {
+    pub fn standard() -> AnnealingParameters {
+        AnnealingParameters {
+            initial_temperature: 2.0,
+            min_temperature: 0.01,
+            alpha: 0.95,
+            moves: 0,
+            patience: 50,
+            reheat: 2.0
+        }
+    }
+}
+
+impl Default for AnnealingParameters {
+    fn default() -> AnnealingParameters {
+        AnnealingParameters::standard()
+    }
+}
+
+/*
+*   The nodes with conflicts, with their position in the list for constant time updates
+*/
+#[derive(Default)]
+pub struct CriticalNodes {
+    pub nodes: Vec<usize>,
+    position: Vec<usize>
+}
+
+impl CriticalNodes {
+    pub fn rebuild(&mut self, x: &Coloring) {
+        self.nodes.clear();
+        self.position.clear();
+        self.position.resize(x.len(), usize::MAX);
+        for i in 0..x.len() {
+            self.update(x, i);
+        }
+    }
+
+    /*
+    *   Add or remove node i after its number of conflicts changed
+    */
+    pub fn update(&mut self, x: &Coloring, i: usize) {
+        let listed = self.position[i] != usize::MAX;
+        if x.conflicts[i] > 0 && !listed {
+            self.position[i] = self.nodes.len();
+            self.nodes.push(i);
+        } else if x.conflicts[i] == 0 && listed {
+            let last = *self.nodes.last().unwrap();
+            self.nodes.swap_remove(self.position[i]);
+            if last != i {
+                self.position[last] = self.position[i];
+            }
+            self.position[i] = usize::MAX;
+        }
+    }
+
+    /*
+    *   Recolor node i and keep the list up to date
+    */
+    pub fn recolor(&mut self, x: &mut Coloring, i: usize, color: usize, g: &Graph) {
+        x.recolor(i, color, g);
+        self.update(x, i);
+        for &neighbor in &g[i] {
+            self.update(x, neighbor);
+        }
+    }
+}
+
+/*
+*   Simulated annealing on a single coloring. Every move gives a random conflicting node a random other
+*   color, which is accepted if it does not add conflicts and otherwise with probability exp(-delta / T).
+*   A generation is one temperature level.
+*/
+pub struct Annealing {
+    pub cooling: Cooling,
+    pub settings: AnnealingParameters,
+    temperature: f64,
+    best_conflicts: usize,
+    best_temperature: f64,
+    stagnation: usize,
+    critical: CriticalNodes
+}
+
+impl Annealing {
+    pub fn new(cooling: Cooling, settings: AnnealingParameters) -> Annealing {
+        Annealing {
+            cooling,
+            settings,
+            temperature: settings.initial_temperature,
+            best_conflicts: usize::MAX,
+            best_temperature: settings.initial_temperature,
+            stagnation: 0,
+            critical: CriticalNodes::default()
+        }
+    }
+}
+
+impl Evolution for Annealing {
+    fn populate(&mut self, g: &Graph, n: usize, k: usize, seed: u64, generation: usize) -> Vec<Coloring> {
+        // Every k starts hot
+        self.temperature = self.settings.initial_temperature;
+        self.best_conflicts = usize::MAX;
+        self.stagnation = 0;
+        g.populate(&mut rng_stream(seed, generation, POPULATE_STREAM), n, k)
+    }
+
+    fn generation(&mut self, g: &Graph, pop: &mut [Coloring], best: &Coloring, k: usize, seed: u64, result: &mut RunResult) {
+        let mut rng = rng_stream(seed, result.generations, 0);
+        let x = &mut pop[0];
+        let moves = if self.settings.moves > 0 { self.settings.moves } else { x.len() * k };
+        self.critical.rebuild(x);
+        for _ in 0..moves {
+            if self.critical.nodes.is_empty() || k < 2 {
+                break;
+            }
+            let i = self.critical.nodes[rng.gen_range(0..self.critical.nodes.len())];
+            let mut color = rng.gen_range(1..k);
+            if color >= x[i] {
+                color += 1;
+            }
+            let delta = g[i].iter().filter(|&&neighbor| x[neighbor] == color).count() as f64 - x.conflicts[i] as f64;
+            if delta <= 0.0 || rng.gen::<f64>() < (-delta / self.temperature).exp() {
+                self.critical.recolor(x, i, color, g);
+            }
+            result.evaluations += 1;
+        }
+
+        if x.tot_conflicts < self.best_conflicts {
+            self.best_conflicts = x.tot_conflicts;
+            self.best_temperature = self.temperature;
+            self.stagnation = 0;
+        } else {
+            self.stagnation += 1;
+        }
+        self.temperature = (self.temperature * self.settings.alpha).max(self.settings.min_temperature);
+        if self.cooling == Cooling::Reheating && self.stagnation >= self.settings.patience {
+            self.temperature = (self.best_temperature * self.settings.reheat).min(self.settings.initial_temperature);
+            self.stagnation = 0;
+            result.restarts += 1;
+        }
+    }
+}
+
+pub fn annealing(g: &Graph, k: usize, cooling: Cooling, settings: AnnealingParameters, stop: Option<usize>, budget: Budget, seed: u64) -> RunResult {
+    let options = Parameters::standard();
+    descend(g, &mut Annealing::new(cooling, settings), &mut SolverState::new(1, k, seed), options, stop, budget, &mut ())
+}
+
+/*
+*   Run simulated annealing on a graph.
+*   Usage: sa <graph> [--cooling geometric|reheating] [--temperature t] [--min-temperature t] [--alpha a] [--moves m]
+*             [--patience p] [--reheat r] [--k n] [--seed s]
+*/
+pub fn annealing_cli(args: &[String]) {
+    let cooling: Cooling = get_flag(args, "--cooling").unwrap_or("geometric").parse().unwrap_or_else(|err| panic!("{}", err));
+    let mut settings = AnnealingParameters::standard();
+    if let Some(value) = get_flag(args, "--temperature") {
+        settings.initial_temperature = value.parse().expect("Invalid temperature");
+    }
+    if let Some(value) = get_flag(args, "--min-temperature") {
+        settings.min_temperature = value.parse().expect("Invalid minimum temperature");
+    }
+    if let Some(value) = get_flag(args, "--alpha") {
+        settings.alpha = value.parse().expect("Invalid alpha");
+    }
+    if let Some(value) = get_flag(args, "--moves") {
+        settings.moves = value.parse().expect("Invalid number of moves");
+    }
+    if let Some(value) = get_flag(args, "--patience") {
+        settings.patience = value.parse().expect("Invalid patience");
+    }
+    if let Some(value) = get_flag(args, "--reheat") {
+        settings.reheat = value.parse().expect("Invalid reheat factor");
+    }
+
+    solver_cli(args, "simulated annealing", 1, || {
+        println!("Cooling: {:?}, initial temperature {}, minimum temperature {}, alpha {}", cooling, settings.initial_temperature, settings.min_temperature, settings.alpha);
+        if cooling == Cooling::Reheating {
+            println!("Reheating to {} times the best temperature after {} levels without improvement", settings.reheat, settings.patience);
+        }
+    }, |graph, k, seed| annealing(graph, k, cooling, settings, None, Budget::standard(), seed));
+}
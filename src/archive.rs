@@ -0,0 +1,130 @@
+use super::structs::*;
+use super::warm::*;
+
+use std::fs;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use serde::{Serialize, Deserialize};
+
+/*
+*   Number of colorings the archive keeps for every k by default
+*/
+pub const ARCHIVE_SIZE: usize = 10;
+
+/*
+*   The first distinct legal colorings (different partitions) found at every k of a run
+*/
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Archive {
+    pub capacity: usize,
+    // In the order they were found
+    pub levels: BTreeMap<usize, Vec<Coloring>>,
+    // Partition hashes of the colorings of every level, rebuilt after a checkpoint is read
+    #[serde(skip)]
+    hashes: BTreeMap<usize, HashSet<u64>>,
+    // The k and the legal flowers (by index) offered last by offer_all
+    #[serde(skip)]
+    offered: (usize, HashMap<usize, Vec<usize>>)
+}
+
+impl Archive {
+    pub fn new(capacity: usize) -> Archive {
+        Archive {capacity, ..Archive::default()}
+    }
+
+    /*
+    *   Keep a copy of a legal coloring with k colors if there is room at k and no coloring of the archive
+    *   has the same partition. Only colorings with the same partition hash are compared in full.
+    *   Returns true if it was kept.
+    */
+    pub fn offer(&mut self, k: usize, x: &Coloring) -> bool {
+        if self.capacity == 0 || x.tot_conflicts > 0 {
+            return false;
+        }
+        let elites = self.levels.entry(k).or_default();
+        if elites.len() >= self.capacity {
+            return false;
+        }
+        let hashes = self.hashes.entry(k).or_default();
+        if hashes.len() != elites.len() {
+            *hashes = elites.iter().map(|elite| elite.partition_hash()).collect();
+        }
+        let hash = x.partition_hash();
+        if hashes.contains(&hash) && elites.iter().any(|elite| elite.same_partition(x)) {
+            return false;
+        }
+        let mut elite = x.clone();
+        elite.lifetime = 0;
+        elites.push(elite);
+        hashes.insert(hash);
+        true
+    }
+
+    /*
+    *   Offer the legal flowers of a population that changed since they were last offered
+    */
+    pub fn offer_all(&mut self, k: usize, pop: &[Coloring]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.offered.0 != k {
+            self.offered = (k, HashMap::new());
+        }
+        for x in pop.iter().filter(|x| x.tot_conflicts == 0) {
+            if self.offered.1.get(&x.index) != Some(&x.solution) {
+                self.offered.1.insert(x.index, x.solution.clone());
+                self.offer(k, x);
+            }
+        }
+    }
+
+    pub fn elites(&self, k: usize) -> &[Coloring] {
+        self.levels.get(&k).map_or(&[], |elites| elites.as_slice())
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels.values().map(|elites| elites.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /*
+    *   Replace flowers of pop other than the one with index keep by the legal colorings of k + 1 reduced
+    *   to k colors (see drop_smallest_class), skipping those with the same partition as that flower
+    */
+    pub fn reinject(&self, g: &Graph, k: usize, pop: &mut [Coloring], keep: usize) {
+        let Some(kept) = pop.iter().find(|x| x.index == keep).cloned() else {
+            return;
+        };
+        let elites = self.elites(k + 1).iter()
+            .map(|elite| drop_smallest_class(g, elite, k))
+            .filter(|elite| !elite.same_partition(&kept));
+        for (x, elite) in pop.iter_mut().filter(|x| x.index != keep).zip(elites) {
+            let index = x.index;
+            x.copy_from(&elite);
+            x.index = index;
+        }
+    }
+
+    /*
+    *   Write all colorings to a JSON file as a list of {k, conflicts, solution}, ordered by k and
+    *   then by conflicts, so tools can pick any of the legal colorings
+    */
+    pub fn write(&self, file_name: &str) {
+        let colorings: Vec<ArchivedColoring> = self.levels.iter()
+            .flat_map(|(&k, elites)| elites.iter().map(move |elite| ArchivedColoring {k, conflicts: elite.tot_conflicts, solution: elite.solution.clone()}))
+            .collect();
+        fs::write(file_name, serde_json::to_string(&colorings).unwrap()).unwrap_or_else(|err| panic!("Could not write archive {}: {}", file_name, err));
+    }
+}
+
+/*
+*   A coloring as exported from the archive, the solution has colors 1..=k for the vertices in order
+*/
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ArchivedColoring {
+    pub k: usize,
+    pub conflicts: usize,
+    pub solution: Vec<usize>
+}
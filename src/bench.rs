@@ -1,175 +1,374 @@
-use std::{time::Instant, ops::Range};
+use std::ops::Range;
 use prettytable::*;
-use std::fs::OpenOptions;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::collections::{HashMap, BTreeMap};
+use serde::{Serialize, Deserialize};
 
 use crate::*;
 
+/*
+*   Generations between the points of the parameter trajectory recorded for a run
+*/
+const TRAJECTORY_INTERVAL: usize = 1000;
 
-static _TESTS_SIMPLE: &'static [(&str, usize)] = &[
-    ("myciel3", 4),
-    ("myciel4", 5),
-    ("queen5_5", 5),
-    ("queen6_6", 6),
-    ("queen7_7", 7),
-    ("myciel5", 6),
-    ("huck", 11),
-    ("jean", 10),
-    ("david", 11),
-    ("myciel6", 7),
-    ("games120", 9),
-    ("miles500", 20),
-    ("miles250", 8),
-    ("anna", 11)
-];
-
-static _TESTS_: &'static [(&str, usize)] = &[
-    ("r250.5", 65)
-];
-
-static _TESTS: &'static [(&str, usize)] = &[
-    ("queen7_7", 7),
-    ("DSJC125.1", 5),
-    ("DSJC125.9", 44),
-    ("queen8_8", 9),
-    ("queen9_9", 10)
-];
-
-static FINAL_TESTS: &'static [(&str, usize)] = &[
-    ("DSJC125.1", 5), 
-    ("DSJC125.5", 17), 
-    ("DSJC125.9", 44), 
-    ("le450_25a", 25),
-    ("le450_25b", 25),
-    ("le450_5a", 5),
-    ("le450_5b", 5),
-    ("le450_5c", 5),
-    ("flat300_28_0", 28),
-    ("r250.5", 65)
-];
-
-pub fn bench_pollinators() {
-
-    let mut table = table!(["Pollinator", "Num_colors", "CPU_time"]);
-
-    bench_single_pol::<CM>(&mut table, "CM");
-
-    bench_single_pol::<CMB>(&mut table, "CMB");
-
-    bench_single_pol::<CMW>(&mut table, "CMW");
-
-    bench_single_pol::<CA>(&mut table, "CA");
-
-    let mut file = std::fs::File::create("out/bench_pollinators.col").expect("");
-    table.print(&mut file).expect("");
+/*
+*   A benchmark suite: which solvers to run with which parameters on which graphs, read from a JSON file.
+*   Solvers are named "<algorithm>:<pollinator>", e.g. "dfpa:CA" or "island:CM".
+*/
+#[derive(Deserialize)]
+pub struct Suite {
+    pub name: String,
+    pub instances: Vec<Instance>,
+    #[serde(default = "default_solvers")]
+    pub solvers: Vec<String>,
+    #[serde(default = "default_parameters")]
+    pub parameters: Vec<Parameters>,
+    #[serde(default)]
+    pub islands: IslandParameters,
+    #[serde(default)]
+    pub hea: HeaParameters,
+    #[serde(default)]
+    pub annealing: AnnealingParameters,
+    #[serde(default)]
+    pub partial: PartialParameters,
+    #[serde(default = "default_repetitions")]
+    pub repetitions: usize,
+    #[serde(default)]
+    pub budget: Budget,
+    #[serde(default = "default_out_dir")]
+    pub out_dir: String,
+    #[serde(default)]
+    pub seed: u64
+}
+
+/*
+*   A graph from the graphs directory with its chromatic number or best known k
+*/
+#[derive(Clone, Deserialize)]
+pub struct Instance {
+    pub graph: String,
+    pub k_star: usize
+}
+
+/*
+*   Everything about a single benchmark run, written as one line of CSV and JSON Lines.
+*   Times are in seconds.
+*/
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub suite: String,
+    pub instance: String,
+    pub k_star: usize,
+    pub solver: String,
+    pub parameters: Parameters,
+    pub seed: u64,
+    pub repetition: usize,
+    // Missing in records of older versions, which are then run again
+    #[serde(default)]
+    pub config: Option<RunConfig>,
+    pub k_init: usize,
+    pub k_final: usize,
+    pub time_to_best: f64,
+    pub total_time: f64,
+    pub generations: usize,
+    pub evaluations: usize,
+    #[serde(default)]
+    pub restarts: usize,
+    #[serde(default)]
+    pub population_restarts: usize,
+    // Flowers restarted with every strategy, see RunResult
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub restarts_by_strategy: BTreeMap<String, usize>,
+    pub git_revision: String,
+    // Parameter values of runs with adaptive control
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trajectory: Vec<ControlPoint>
+}
+
+/*
+*   The settings of a run besides its parameters: the budget, the k it stops at and the
+*   settings of the solver from the suite, null for solvers without any
+*/
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RunConfig {
+    pub budget: Budget,
+    pub stop: Option<usize>,
+    pub settings: serde_json::Value
+}
+
+impl RunConfig {
+    pub fn new(solver: &str, suite: &Suite, stop: Option<usize>) -> RunConfig {
+        let algorithm = solver.split_once(':').map_or(solver, |(algorithm, _)| algorithm);
+        let settings = match algorithm {
+            "island" => serde_json::to_value(suite.islands),
+            "hea" => serde_json::to_value(suite.hea),
+            "sa" => serde_json::to_value(suite.annealing),
+            "partial" => serde_json::to_value(suite.partial),
+            _ => Ok(serde_json::Value::Null)
+        };
+        RunConfig {budget: suite.budget, stop, settings: settings.unwrap()}
+    }
+}
+
+/*
+*   Identifies a run within a suite: instance, solver, parameters and run config (as JSON) and seed
+*/
+pub type RunKey = (String, String, String, String, u64);
+
+impl RunRecord {
+    const CSV_HEADER: &'static str = "suite,instance,k_star,solver,lambda,beta,switch_p,lifetime_limit,pop_size,min_distance,restart,stagnation_limit,seed,repetition,k_init,k_final,time_to_best,total_time,generations,evaluations,restarts,population_restarts,git_revision";
+
+    pub fn key(&self) -> RunKey {
+        run_key(&self.instance, &self.solver, &self.parameters, self.config.as_ref(), self.seed)
+    }
+
+    pub fn to_csv(&self) -> String {
+        let p = &self.parameters;
+        format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{:.6},{:.6},{},{},{},{},{}",
+            self.suite, self.instance, self.k_star, self.solver, p.lambda, p.beta.map_or(String::new(), |beta| beta.to_string()), p.switch_p, p.lifetime_limit, p.pop_size, p.min_distance,
+            p.restart, p.stagnation_limit, self.seed, self.repetition, self.k_init, self.k_final, self.time_to_best, self.total_time,
+            self.generations, self.evaluations, self.restarts, self.population_restarts, self.git_revision)
+    }
+}
+
+pub fn run_key(instance: &str, solver: &str, parameters: &Parameters, config: Option<&RunConfig>, seed: u64) -> RunKey {
+    (instance.to_string(), solver.to_string(), serde_json::to_string(parameters).unwrap(), serde_json::to_string(&config).unwrap(), seed)
+}
+
+/*
+*   Read the records of earlier runs from <out_dir>/<name>.jsonl, if any. A line cut off by
+*   killing the process mid-write is skipped, so that run is simply done again.
+*/
+pub fn completed_runs(out_dir: &str, name: &str) -> HashMap<RunKey, RunRecord> {
+    let path = format!("{}/{}.jsonl", out_dir, name);
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    content.lines()
+        .filter_map(|line| match serde_json::from_str::<RunRecord>(line) {
+            Ok(record) => Some((record.key(), record)),
+            Err(_) => {
+                if !line.trim().is_empty() {
+                    println!("Ignoring incomplete record in {}", path);
+                }
+                None
+            }
+        })
+        .collect()
 }
 
-fn bench_single_pol<T: Pollinator>(table: &mut prettytable::Table, name: &str) {
-    println!("Evaluating {}:", name);
-    let now = Instant::now();
-    let num_colors = benchmark::<T>(None, None);
-    let elapsed_time = now.elapsed().as_millis();
-    table.add_row(row![name, num_colors, elapsed_time]);
-    println!("");
-}
-
-pub fn bench_parameters() {
-    let lambdas = [1.0, 1.25, 1.5, 1.75];
-    let switches = [0.2, 0.4, 0.6, 0.8];
-    //let lifetimes = [10, 30, 50, 70];
-
-    let mut options = Parameters::standard();
-
-    let mut table =  table!(["lambda", "switch_p", "num_colors","CPU_time"]);
-
-    //let mut best = (0.0, 0.0);
-    //let mut best_coloring = f32::INFINITY;
-
-    for lambda in lambdas {
-        for switch_p in switches {
-            println!("Evaluating lambda {}, switch_p {}", lambda, switch_p);
-            options.lambda = lambda;
-            options.switch_p = switch_p;
-            let now = Instant::now();
-            let num_colors = benchmark::<CA>(Some(options), None);
-            let elapsed_time = now.elapsed().as_millis();
-            table.add_row(row![lambda, switch_p, num_colors, elapsed_time]);
-            //if num_colors < best_coloring {
-            //    best_coloring = num_colors;
-            //    best = (lambda, switch_p);
-            //}
-            println!("");
+/*
+*   Appends run records to <out_dir>/<name>.csv and <out_dir>/<name>.jsonl as soon as they are finished.
+*   The JSON Lines file is the one runs are resumed from, so it is written first.
+*/
+pub struct RecordWriter {
+    csv: File,
+    jsonl: File
+}
+
+impl RecordWriter {
+    pub fn open(out_dir: &str, name: &str) -> RecordWriter {
+        fs::create_dir_all(out_dir).unwrap_or_else(|err| panic!("Could not create {}: {}", out_dir, err));
+        let csv_path = format!("{}/{}.csv", out_dir, name);
+        let write_header = fs::metadata(&csv_path).map_or(true, |meta| meta.len() == 0);
+        let mut csv = append(&csv_path);
+        if write_header {
+            writeln!(csv, "{}", RunRecord::CSV_HEADER).expect("Could not write results");
         }
+        let jsonl_path = format!("{}/{}.jsonl", out_dir, name);
+        let cut_off = fs::read(&jsonl_path).is_ok_and(|content| content.last().is_some_and(|&c| c != b'\n'));
+        let mut jsonl = append(&jsonl_path);
+        if cut_off {
+            // Terminate the line of a run that was interrupted while it was being written
+            writeln!(jsonl).expect("Could not write results");
+        }
+        RecordWriter {csv, jsonl}
     }
-    let mut file = std::fs::File::create("out/CA/bench_lambda-switch.col").expect("");
-    table.print(&mut file).expect("");
 
-    //options.lambda = best.0;
-    //options.switch_p = best.1;
-//
-    //println!("");
-
-    //let mut table =  table!(["lifetimes", "num_colors","CPU_time"]);
-//
-    //for lifetime_limit in lifetimes {
-    //    println!("Evaluating lifetime_limit: {}", lifetime_limit);
-    //    options.lifetime_limit = lifetime_limit;
-    //    let now = Instant::now();
-    //    let num_colors = benchmark::<CM>(Some(options));
-    //    let elapsed_time = now.elapsed().as_millis();
-    //    table.add_row(row![lifetime_limit, num_colors, elapsed_time]);
-    //}
-    //let mut file = std::fs::File::create("out/bench_lifetimes.col").expect("");
-    //table.print(&mut file).expect("");
-
-}
-
-pub fn benchmark<T: Pollinator>(options: Option<Parameters>, range: Option<Range<usize>>) -> f32 {
-    let mut tot_colors = 0.0;
-    let mut table = table!(["Graph", "k*", "k_init", "DFPA", "Best", "Average"]);
-
-    let graphs = match range {
-        Some(range) => FINAL_TESTS[range].iter(),
-        None => FINAL_TESTS.iter()
+    pub fn write(&mut self, record: &RunRecord) {
+        writeln!(self.jsonl, "{}", serde_json::to_string(record).unwrap()).expect("Could not write results");
+        self.jsonl.flush().expect("Could not write results");
+        writeln!(self.csv, "{}", record.to_csv()).expect("Could not write results");
+        self.csv.flush().expect("Could not write results");
+    }
+}
+
+/*
+*   Open a file for appending, creating it if it does not exist
+*/
+pub fn append(path: &str) -> File {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap_or_else(|err| panic!("Could not open {}: {}", path, err))
+}
+
+fn default_solvers() -> Vec<String> {
+    vec![String::from("dfpa:CA")]
+}
+
+fn default_parameters() -> Vec<Parameters> {
+    vec![Parameters::standard()]
+}
+
+fn default_repetitions() -> usize {
+    10
+}
+
+fn default_out_dir() -> String {
+    String::from("out")
+}
+
+impl Suite {
+    /*
+    *   Read and parse a suite from the specified file
+    */
+    pub fn read(file_name: &str) -> Suite {
+        let content = fs::read_to_string(file_name).unwrap_or_else(|err| panic!("Could not read suite {}: {}", file_name, err));
+        serde_json::from_str(&content).unwrap_or_else(|err| panic!("Invalid suite {}: {}", file_name, err))
+    }
+}
+
+/*
+*   Run a solver by name, "<algorithm>:<variant>". For dfpa and island the variant is a registered
+*   pollinator or a combination of operators, e.g. "dfpa:CA" or "island:levy_pop1+tabucol".
+*   "hea" runs the Hybrid Evolutionary Algorithm, "sa:geometric" or "sa:reheating" simulated annealing
+*   "ils:<local operator>" Iterated Local Search, e.g. "ils:tabucol", and "partial" DFPA on partial legal colorings.
+*   The other settings come from the suite. The observer follows dfpa runs, the solver with adaptive control.
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn run_solver(solver: &str, g: &Graph, k: usize, options: Parameters, stop: Option<usize>, suite: &Suite, seed: u64, observer: &mut dyn Observer) -> RunResult {
+    let (algorithm, variant) = match solver.split_once(':') {
+        Some((algorithm, variant)) => (algorithm, Some(variant)),
+        None => (solver, None)
     };
+    let pollinator = || Pollinator::by_name(variant.unwrap_or("CA")).unwrap_or_else(|err| panic!("{}", err));
+    let budget = suite.budget;
+    match algorithm {
+        "dfpa" => continue_fpa(g, &pollinator(), &mut SolverState::new(options.pop_size, k, seed), options, stop, budget, observer),
+        "island" => island_fpa(g, &pollinator(), k, Some(options), suite.islands, stop, budget, seed),
+        "hea" => hea(g, k, Some(options), suite.hea, stop, budget, seed),
+        "sa" => {
+            let cooling = variant.unwrap_or("geometric").parse().unwrap_or_else(|err| panic!("{}", err));
+            annealing(g, k, cooling, suite.annealing, stop, budget, seed)
+        }
+        "ils" => iterated_local_search(g, variant.unwrap_or("tabucol"), k, Some(options), stop, budget, seed),
+        "partial" => partial_fpa(g, k, Some(options), suite.partial, stop, budget, seed),
+        _ => panic!("Unknown algorithm {}, use dfpa, island, hea, sa, ils or partial", algorithm)
+    }
+}
 
-    for &(filename, chrom) in graphs {
-        const NUM_TRIES: usize = 10;
-        let path = format!("graphs/{}.col", filename);
-        let graph = Graph::read(&path);
-        //let max_d = graph.max_degree();
-        //println!("Evaluating {}", filename);
-        //println!("Num nodes: {}", graph.len());
-        //println!("Max degree: {}", max_d);
-
-        let mut avg_color = 0;
-        
+/*
+*   Run every solver with every parameter set on the instances of a suite (or a range of them).
+*   The tables are printed and appended to <out_dir>/<name>.col, every run is also recorded in
+*   <out_dir>/<name>.csv and <out_dir>/<name>.jsonl. Repetition i of every configuration uses seed + i.
+*   Runs already recorded in <out_dir>/<name>.jsonl with the same parameters, budget and solver settings
+*   are not run again, which resumes an interrupted campaign.
+*/
+pub fn run_suite(suite: &Suite, range: Option<Range<usize>>) {
+    let instances = match range {
+        Some(range) => &suite.instances[range],
+        None => &suite.instances[..]
+    };
+    let graphs: Vec<(&Instance, Graph, usize)> = instances.iter().map(|instance| {
+        let graph = Graph::read(&format!("graphs/{}.col", instance.graph));
         let (greedy, _) = dsatur2(&graph);
+        (instance, graph, greedy)
+    }).collect();
 
-        let mut tries = Vec::new();
+    let mut table = table!(["Graph", "k*", "k_init", "Solver", "Parameters", "Runs", "Best", "Average", "Avg_time"]);
+    let mut summary = table!(["Solver", "Parameters", "Num_colors", "CPU_time"]);
+    let completed = completed_runs(&suite.out_dir, &suite.name);
+    if !completed.is_empty() {
+        println!("Resuming {}: {} runs already completed", suite.name, completed.len());
+    }
+    let mut records = RecordWriter::open(&suite.out_dir, &suite.name);
 
-        let now = Instant::now();
-        for it in 0..NUM_TRIES {
-            println!("Iteration: {}/{}", it+1, NUM_TRIES);
-            //let num_colors = discrete_fpa::<T>(&graph, POP_SIZE, max_d, options, Some(chrom));
-            let num_colors = discrete_fpa::<T>(&graph, POP_SIZE, greedy-1, options, Some(chrom));
-            avg_color += num_colors;
-            tries.push(num_colors);
+    for solver in &suite.solvers {
+        for &options in &suite.parameters {
+            println!("Evaluating {} with {}:", solver, options);
+            let mut tot_colors = 0.0;
+            let mut config_time = 0.0;
+            for (instance, graph, greedy) in &graphs {
+                let mut tries = Vec::new();
+                let mut instance_time = 0.0;
+                for it in 0..suite.repetitions {
+                    let seed = suite.seed + it as u64;
+                    let config = RunConfig::new(solver, suite, Some(instance.k_star));
+                    let record = match completed.get(&run_key(&instance.graph, solver, &options, Some(&config), seed)) {
+                        Some(record) => {
+                            println!("Iteration: {}/{} (completed)", it+1, suite.repetitions);
+                            record.clone()
+                        }
+                        None => {
+                            println!("Iteration: {}/{}", it+1, suite.repetitions);
+                            let mut trajectory = TrajectoryLog::new(TRAJECTORY_INTERVAL);
+                            let result = run_solver(solver, graph, greedy-1, options, Some(instance.k_star), suite, seed, &mut trajectory);
+                            let record = RunRecord {
+                                suite: suite.name.clone(),
+                                instance: instance.graph.clone(),
+                                k_star: instance.k_star,
+                                solver: solver.clone(),
+                                parameters: options,
+                                seed,
+                                repetition: it,
+                                config: Some(config),
+                                k_init: greedy-1,
+                                k_final: result.k,
+                                time_to_best: result.time_to_best,
+                                total_time: result.total_time,
+                                generations: result.generations,
+                                evaluations: result.evaluations,
+                                restarts: result.restarts,
+                                population_restarts: result.population_restarts,
+                                restarts_by_strategy: result.restarts_by_strategy.clone(),
+                                git_revision: String::from(env!("GIT_REVISION")),
+                                trajectory: trajectory.points
+                            };
+                            records.write(&record);
+                            record
+                        }
+                    };
+                    instance_time += record.total_time;
+                    tries.push(record.k_final);
+                }
+                config_time += instance_time;
+                let avg_time = instance_time * 1000.0 / suite.repetitions as f64;
+                let avg_color = tries.iter().sum::<usize>() as f32 / suite.repetitions as f32;
+                tot_colors += avg_color;
+                println!("Graph: {}, Chromatic number: {}, Average number of colors: {}, Greedy solution: {}, Average execution time: {}", instance.graph, instance.k_star, avg_color, greedy, avg_time);
+                table.add_row(row![instance.graph, instance.k_star, greedy-1, solver, options, format!("{:?}", tries), tries.iter().min().unwrap(), avg_color, format!("{:.0}", avg_time)]);
+            }
+            summary.add_row(row![solver, options, tot_colors, format!("{:.0}", config_time * 1000.0)]);
+            println!();
         }
-        let elapsed_time = now.elapsed();
-        let avg_time = elapsed_time.as_millis() as f32 / NUM_TRIES as f32;
-        let avg_color = avg_color as f32 / NUM_TRIES as f32;
-        tot_colors += avg_color;
-        println!("Graph: {}, Chromatic number: {}, Average number of colors: {}, Greedy solution: {}, Average execution time: {}", filename, chrom, avg_color, greedy, avg_time);
-        table.add_row(row![filename, chrom, greedy-1, format!("{:?}", tries), tries.iter().min().unwrap(), avg_color,]);
     }
-    //let mut file = std::fs::File::create("out/final.col").expect(""); // Create new file
-    let mut file = OpenOptions::new()
-        .write(true)
-        .append(true)
-        .open("out/final.col")
-        .unwrap();
-    
+
+    summary.printstd();
+
+    let mut file = append(&format!("{}/{}.col", suite.out_dir, suite.name));
+
     table.print(&mut file).expect("");
-    
-    tot_colors
-}
\ No newline at end of file
+    summary.print(&mut file).expect("");
+}
+
+/*
+*   Run a benchmark suite.
+*   Usage: bench [suite file] [from [to]]
+*/
+pub fn bench(args: &[String], default_suite: &str) {
+    let positional = positional_args(args);
+    let (file_name, range) = match positional.first() {
+        Some(&first) if first.parse::<usize>().is_err() => (first, &positional[1..]),
+        _ => (default_suite, &positional[..])
+    };
+    let range = range.first().map(|from| {
+        let from: usize = from.parse().expect("Invalid instance index");
+        let to = match range.get(1) {
+            Some(to) => to.parse::<usize>().expect("Invalid instance index") + 1,
+            None => from + 1
+        };
+        from..to
+    });
+    run_suite(&Suite::read(file_name), range);
+}
@@ -0,0 +1,114 @@
+use super::*;
+
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Serialize, Deserialize};
+
+/*
+*   Everything DFPA needs to continue a run. Random numbers are drawn from streams derived from the
+*   seed and the generation counter (see rng_stream), so those two make up the complete RNG state.
+*/
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SolverState {
+    pub seed: u64,
+    pub pop_size: usize,
+    pub k: usize,
+    // Generations spent on the current k
+    pub iteration: usize,
+    // Empty until the population for the current k is drawn
+    pub pop: Vec<Coloring>,
+    // Best legal coloring found so far
+    pub best: Option<Coloring>,
+    // Best distinct colorings found at every k
+    #[serde(default)]
+    pub archive: Archive,
+    // Parameter control and operator qualities of adaptive runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive: Option<AdaptiveState>,
+    // Progress towards the stagnation limit
+    #[serde(default)]
+    pub stagnation: Stagnation,
+    pub result: RunResult,
+    // Seconds the run has been going when the state was saved
+    pub elapsed: f64
+}
+
+impl SolverState {
+    pub fn new(pop_size: usize, k: usize, seed: u64) -> SolverState {
+        SolverState {seed, pop_size, k, iteration: 0, pop: Vec::new(), best: None, archive: Archive::default(), adaptive: None, stagnation: Stagnation::default(), result: RunResult::new(k + 1), elapsed: 0.0}
+    }
+}
+
+/*
+*   Generations the best flower of the population did not improve for k
+*/
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Stagnation {
+    pub k: usize,
+    pub best_conflicts: usize,
+    pub generations: usize
+}
+
+/*
+*   A solver state together with the settings of the run, as written to a checkpoint file
+*/
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub graph: String,
+    pub pollinator: String,
+    pub parameters: Parameters,
+    pub budget: Budget,
+    pub stop: Option<usize>,
+    pub state: SolverState
+}
+
+impl Checkpoint {
+    pub fn read(file_name: &str) -> Checkpoint {
+        let content = fs::read_to_string(file_name).unwrap_or_else(|err| panic!("Could not read checkpoint {}: {}", file_name, err));
+        serde_json::from_str(&content).unwrap_or_else(|err| panic!("Invalid checkpoint {}: {}", file_name, err))
+    }
+
+    /*
+    *   Write the checkpoint next to the file first and then move it into place,
+    *   so being killed while writing never leaves a broken checkpoint behind
+    */
+    pub fn write(&self, file_name: &str) {
+        let temporary = format!("{}.tmp", file_name);
+        fs::write(&temporary, serde_json::to_string(self).unwrap()).unwrap_or_else(|err| panic!("Could not write checkpoint {}: {}", temporary, err));
+        fs::rename(&temporary, file_name).unwrap_or_else(|err| panic!("Could not write checkpoint {}: {}", file_name, err));
+    }
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/*
+*   Saves the state of a run every interval generations and when the process receives SIGINT
+*/
+pub struct Checkpointer {
+    pub file_name: String,
+    pub interval: usize,
+    pub template: Checkpoint
+}
+
+impl Checkpointer {
+    /*
+    *   Catch SIGINT (Ctrl-C), the running solver then saves a checkpoint and stops
+    */
+    pub fn catch_interrupt() {
+        ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst)).expect("Could not set the SIGINT handler");
+    }
+
+    pub fn interrupted() -> bool {
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+}
+
+impl Observer for Checkpointer {
+    fn generation(&mut self, state: &SolverState, stats: &GenerationStats) -> bool {
+        let interrupted = Checkpointer::interrupted();
+        if interrupted || (self.interval > 0 && state.result.generations.is_multiple_of(self.interval)) {
+            Checkpoint { state: state.clone(), ..self.template.clone() }.write(&self.file_name);
+        }
+        interrupted
+    }
+}
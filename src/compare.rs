@@ -0,0 +1,193 @@
+use super::bench::*;
+use super::statistics::*;
+use super::*;
+
+use std::fs;
+use std::io::Write;
+use itertools::Itertools;
+use prettytable::*;
+
+/*
+*   Benchmark records grouped by instance and configuration (solver and parameters)
+*/
+pub struct Results {
+    pub instances: Vec<(String, usize)>,
+    pub configs: Vec<String>,
+    pub runs: Vec<Vec<Vec<RunRecord>>>
+}
+
+impl Results {
+    /*
+    *   Read the records of one or more JSON Lines files
+    */
+    pub fn read(file_names: &[&str]) -> Results {
+        let mut records = Vec::new();
+        for file_name in file_names {
+            let content = fs::read_to_string(file_name).unwrap_or_else(|err| panic!("Could not read {}: {}", file_name, err));
+            for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                match serde_json::from_str::<RunRecord>(line) {
+                    Ok(record) => records.push(record),
+                    Err(err) => println!("Ignoring invalid record in {}: {}", file_name, err)
+                }
+            }
+        }
+        Results::group(records)
+    }
+
+    pub fn group(records: Vec<RunRecord>) -> Results {
+        let distinct_parameters = records.iter().map(|record| record.parameters.to_string()).unique().count();
+        let label = |record: &RunRecord| if distinct_parameters > 1 {
+            format!("{} [{}]", record.solver, record.parameters)
+        } else {
+            record.solver.clone()
+        };
+
+        let instances: Vec<(String, usize)> = records.iter().map(|record| (record.instance.clone(), record.k_star)).unique_by(|(name, _)| name.clone()).collect();
+        let configs: Vec<String> = records.iter().map(label).unique().collect();
+        let mut runs = vec![vec![Vec::new(); configs.len()]; instances.len()];
+        for record in records {
+            let i = instances.iter().position(|(name, _)| *name == record.instance).unwrap();
+            let j = configs.iter().position(|config| *config == label(&record)).unwrap();
+            runs[i][j].push(record);
+        }
+        Results {instances, configs, runs}
+    }
+}
+
+/*
+*   Compare the configurations of benchmark results and write the report as text and CSV.
+*   Usage: compare <results.jsonl>... [--out <dir>]
+*/
+pub fn compare(args: &[String]) {
+    let files = positional_args(args);
+    assert!(!files.is_empty(), "Missing results file");
+    let out_dir = match get_flag(args, "--out") {
+        Some(dir) => dir.to_string(),
+        None => std::path::Path::new(files[0]).parent().map_or(String::from("."), |dir| dir.display().to_string())
+    };
+    fs::create_dir_all(&out_dir).unwrap_or_else(|err| panic!("Could not create {}: {}", out_dir, err));
+    let results = Results::read(&files);
+
+    summary(&results, &out_dir);
+    rank_sum_tests(&results, &out_dir);
+    friedman_test(&results, &out_dir);
+    time_to_target(&results, &out_dir);
+}
+
+/*
+*   Number of colors, success rate (reaching k*), times and mean restarts per instance and configuration
+*/
+fn summary(results: &Results, out_dir: &str) {
+    let mut table = table!(["Graph", "k*", "Config", "Runs", "Best", "Average", "Success", "Avg_time", "Restarts", "Pop_restarts"]);
+    let mut csv = vec![String::from("instance,k_star,config,runs,best_k,mean_k,success_rate,mean_time,mean_restarts,mean_population_restarts")];
+    for ((instance, k_star), configs) in results.instances.iter().zip(&results.runs) {
+        for (config, runs) in results.configs.iter().zip(configs) {
+            if runs.is_empty() {
+                continue;
+            }
+            let k: Vec<f64> = runs.iter().map(|run| run.k_final as f64).collect();
+            let best = runs.iter().map(|run| run.k_final).min().unwrap();
+            let success = runs.iter().filter(|run| run.k_final <= *k_star).count() as f64 / runs.len() as f64;
+            let time = mean(&runs.iter().map(|run| run.total_time).collect::<Vec<f64>>());
+            let restarts = mean(&runs.iter().map(|run| run.restarts as f64).collect::<Vec<f64>>());
+            let population_restarts = mean(&runs.iter().map(|run| run.population_restarts as f64).collect::<Vec<f64>>());
+            table.add_row(row![instance, k_star, config, runs.len(), best, format!("{:.2}", mean(&k)), format!("{:.0}%", success * 100.0), format!("{:.2}", time),
+                format!("{:.1}", restarts), format!("{:.1}", population_restarts)]);
+            csv.push(format!("{},{},{},{},{},{:.4},{:.4},{:.6},{:.2},{:.2}", instance, k_star, config, runs.len(), best, mean(&k), success, time, restarts, population_restarts));
+        }
+    }
+    println!("Results per instance:");
+    table.printstd();
+    write_csv(out_dir, "compare_summary.csv", &csv);
+}
+
+/*
+*   Pairwise Wilcoxon rank-sum / Mann-Whitney U tests on the number of colors for every instance
+*/
+fn rank_sum_tests(results: &Results, out_dir: &str) {
+    let mut table = table!(["Graph", "Config A", "Config B", "U", "z", "p"]);
+    let mut csv = vec![String::from("instance,config_a,config_b,u,z,p_value")];
+    for ((instance, _), configs) in results.instances.iter().zip(&results.runs) {
+        for a in 0..configs.len() {
+            for b in a+1..configs.len() {
+                if configs[a].is_empty() || configs[b].is_empty() {
+                    continue;
+                }
+                let k_a: Vec<f64> = configs[a].iter().map(|run| run.k_final as f64).collect();
+                let k_b: Vec<f64> = configs[b].iter().map(|run| run.k_final as f64).collect();
+                let test = mann_whitney(&k_a, &k_b);
+                let marker = if test.p_value < 0.05 { "*" } else { "" };
+                table.add_row(row![instance, results.configs[a], results.configs[b], test.u, format!("{:.3}", test.z), format!("{:.4}{}", test.p_value, marker)]);
+                csv.push(format!("{},{},{},{},{:.6},{:.6}", instance, results.configs[a], results.configs[b], test.u, test.z, test.p_value));
+            }
+        }
+    }
+    println!("Mann-Whitney U tests on the number of colors (* p < 0.05):");
+    table.printstd();
+    write_csv(out_dir, "compare_mannwhitney.csv", &csv);
+}
+
+/*
+*   Friedman test over the instances every configuration was run on, with Nemenyi post-hoc ranks
+*/
+fn friedman_test(results: &Results, out_dir: &str) {
+    let blocks: Vec<Vec<f64>> = results.runs.iter()
+        .filter(|configs| configs.iter().all(|runs| !runs.is_empty()))
+        .map(|configs| configs.iter().map(|runs| mean(&runs.iter().map(|run| run.k_final as f64).collect::<Vec<f64>>())).collect())
+        .collect();
+    let test = friedman(&blocks);
+    let cd = nemenyi_critical_difference(results.configs.len(), blocks.len());
+
+    println!("Friedman test over {} instances: chi2 = {:.4}, p = {:.4}", blocks.len(), test.statistic, test.p_value);
+    match cd {
+        Some(cd) => println!("Nemenyi critical difference (alpha = 0.05): {:.4}", cd),
+        None => println!("Nemenyi critical difference is only tabulated for 2 to 10 configurations")
+    }
+    let best_rank = test.average_ranks.iter().copied().fold(f64::INFINITY, f64::min);
+    let mut table = table!(["Config", "Average rank", "Differs from best"]);
+    let mut csv = vec![String::from("config,average_rank,differs_from_best")];
+    for (config, rank) in results.configs.iter().zip(&test.average_ranks) {
+        let differs = cd.map_or(String::from("-"), |cd| (rank - best_rank > cd).to_string());
+        table.add_row(row![config, format!("{:.3}", rank), differs]);
+        csv.push(format!("{},{:.6},{}", config, rank, differs));
+    }
+    table.printstd();
+    write_csv(out_dir, "compare_friedman.csv", &csv);
+}
+
+/*
+*   Empirical time-to-target distributions, the target is reaching k*. Runs that never reach it
+*   count towards the total, so the distribution ends at the success rate.
+*/
+fn time_to_target(results: &Results, out_dir: &str) {
+    let mut table = table!(["Graph", "Config", "Successes", "Min", "Median", "Max"]);
+    let mut csv = vec![String::from("instance,config,time,probability")];
+    for ((instance, k_star), configs) in results.instances.iter().zip(&results.runs) {
+        for (config, runs) in results.configs.iter().zip(configs) {
+            if runs.is_empty() {
+                continue;
+            }
+            let times: Vec<f64> = runs.iter()
+                .filter(|run| run.k_final <= *k_star)
+                .map(|run| run.time_to_best)
+                .sorted_by(|a, b| a.total_cmp(b))
+                .collect();
+            for (i, time) in times.iter().enumerate() {
+                csv.push(format!("{},{},{:.6},{:.6}", instance, config, time, (i + 1) as f64 / runs.len() as f64));
+            }
+            let quantile = |q: f64| times.get(((times.len() as f64 - 1.0) * q).round() as usize).map_or(String::from("-"), |t| format!("{:.3}", t));
+            table.add_row(row![instance, config, format!("{}/{}", times.len(), runs.len()), quantile(0.0), quantile(0.5), quantile(1.0)]);
+        }
+    }
+    println!("Time to target (seconds until k* was reached):");
+    table.printstd();
+    write_csv(out_dir, "compare_ttt.csv", &csv);
+}
+
+fn write_csv(out_dir: &str, name: &str, lines: &[String]) {
+    let path = format!("{}/{}", out_dir, name);
+    let mut file = fs::File::create(&path).unwrap_or_else(|err| panic!("Could not create {}: {}", path, err));
+    for line in lines {
+        writeln!(file, "{}", line).expect("Could not write report");
+    }
+}
@@ -0,0 +1,252 @@
+use super::structs::*;
+use super::registry::*;
+use super::levy::*;
+
+use std::fmt;
+use std::str::FromStr;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+/*
+*   How switch_p and lambda develop during a run. The schedules start at the values of the
+*   parameters and reach the given values after the given number of generations of the run.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "lowercase")]
+pub enum Control {
+    #[default]
+    Fixed,
+    Linear { switch_p: f64, lambda: f32, generations: usize },
+    // Changes slowly at the start and the end, fastest halfway
+    Cosine { switch_p: f64, lambda: f32, generations: usize },
+    // 1/5th rule: every window generations lambda is multiplied by factor if more than a fifth
+    // of the pollinations improved their flower, and divided by it if fewer did
+    Success { factor: f32, window: usize }
+}
+
+impl Control {
+    pub fn is_fixed(&self) -> bool {
+        *self == Control::Fixed
+    }
+}
+
+/*
+*   Parse "fixed", "linear:<switch_p>:<lambda>:<generations>", "cosine:<switch_p>:<lambda>:<generations>"
+*   or "success:<factor>:<window>"
+*/
+impl FromStr for Control {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Control, String> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let invalid = || format!("Invalid control {}, use fixed, linear:<switch_p>:<lambda>:<generations>, cosine:<switch_p>:<lambda>:<generations> or success:<factor>:<window>", s);
+        match parts[..] {
+            ["fixed"] => Ok(Control::Fixed),
+            ["linear", switch_p, lambda, generations] => Ok(Control::Linear {
+                switch_p: switch_p.parse().map_err(|_| invalid())?,
+                lambda: lambda.parse().map_err(|_| invalid())?,
+                generations: generations.parse().map_err(|_| invalid())?
+            }),
+            ["cosine", switch_p, lambda, generations] => Ok(Control::Cosine {
+                switch_p: switch_p.parse().map_err(|_| invalid())?,
+                lambda: lambda.parse().map_err(|_| invalid())?,
+                generations: generations.parse().map_err(|_| invalid())?
+            }),
+            ["success", factor, window] => Ok(Control::Success {
+                factor: factor.parse().map_err(|_| invalid())?,
+                window: window.parse().map_err(|_| invalid())?
+            }),
+            _ => Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for Control {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Control::Fixed => write!(f, "fixed"),
+            Control::Linear { switch_p, lambda, generations } => write!(f, "linear:{}:{}:{}", switch_p, lambda, generations),
+            Control::Cosine { switch_p, lambda, generations } => write!(f, "cosine:{}:{}:{}", switch_p, lambda, generations),
+            Control::Success { factor, window } => write!(f, "success:{}:{}", factor, window)
+        }
+    }
+}
+
+/*
+*   The parameter values used in one generation of a run. operators holds the mean selection
+*   probabilities of an adaptive local operator, if there is one.
+*/
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ControlPoint {
+    pub generation: usize,
+    pub k: usize,
+    pub switch_p: f64,
+    pub lambda: f32,
+    // Share of the pollinations of the last window that improved their flower
+    pub success_rate: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operators: Vec<f64>
+}
+
+/*
+*   Applies a control strategy to the parameters of a run
+*/
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Controller {
+    pub control: Control,
+    // The parameters the run started with
+    base: Parameters,
+    successes: usize,
+    attempts: usize,
+    success_rate: f64,
+    generation: usize,
+    k: usize
+}
+
+impl Controller {
+    pub fn new(options: Parameters) -> Controller {
+        Controller {control: options.control, base: options, successes: 0, attempts: 0, success_rate: 0.0, generation: 0, k: 0}
+    }
+
+    /*
+    *   Set switch_p and lambda for a generation of the run
+    */
+    pub fn adjust(&mut self, options: &mut Parameters, levy: &mut LevyFlight, generation: usize, k: usize) {
+        self.generation = generation;
+        self.k = k;
+        match self.control {
+            Control::Fixed => {}
+            Control::Linear { switch_p, lambda, generations } => {
+                let t = (generation as f64 / generations.max(1) as f64).min(1.0);
+                options.switch_p = self.base.switch_p + (switch_p - self.base.switch_p) * t;
+                options.lambda = self.base.lambda + (lambda - self.base.lambda) * t as f32;
+            }
+            Control::Cosine { switch_p, lambda, generations } => {
+                let t = (generation as f64 / generations.max(1) as f64).min(1.0);
+                let weight = (1.0 + (std::f64::consts::PI * t).cos()) / 2.0;
+                options.switch_p = switch_p + (self.base.switch_p - switch_p) * weight;
+                options.lambda = lambda + (self.base.lambda - lambda) * weight as f32;
+            }
+            Control::Success { factor, window } => {
+                if generation > 0 && generation.is_multiple_of(window.max(1)) && self.attempts > 0 {
+                    self.success_rate = self.successes as f64 / self.attempts as f64;
+                    if self.success_rate > 0.2 {
+                        options.lambda = (options.lambda * factor).min(100.0);
+                    } else if self.success_rate < 0.2 {
+                        options.lambda = (options.lambda / factor).max(0.1);
+                    }
+                    self.successes = 0;
+                    self.attempts = 0;
+                }
+            }
+        }
+        levy.lambda = options.lambda;
+    }
+
+    /*
+    *   Count the pollinations of a generation and how many of them improved their flower
+    */
+    pub fn record(&mut self, successes: usize, attempts: usize) {
+        self.successes += successes;
+        self.attempts += attempts;
+        if !matches!(self.control, Control::Success { .. }) {
+            self.success_rate = successes as f64 / attempts.max(1) as f64;
+            self.successes = 0;
+            self.attempts = 0;
+        }
+    }
+
+    /*
+    *   Continue with the controller of a resumed run
+    */
+    pub fn restore(&mut self, options: &mut Parameters, levy: &mut LevyFlight, state: &AdaptiveState) {
+        *self = state.controller.clone();
+        options.switch_p = state.switch_p;
+        options.lambda = state.lambda;
+        levy.lambda = state.lambda;
+    }
+
+    pub fn point(&self, options: &Parameters, operators: Vec<f64>) -> ControlPoint {
+        ControlPoint {
+            generation: self.generation,
+            k: self.k,
+            switch_p: options.switch_p,
+            lambda: options.lambda,
+            success_rate: self.success_rate,
+            operators
+        }
+    }
+}
+
+/*
+*   What DFPA adapted during a run besides the population, as saved in checkpoints: the controller,
+*   the parameter values it set and the qualities of the adaptive local operator of every flower
+*/
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AdaptiveState {
+    pub controller: Controller,
+    pub switch_p: f64,
+    pub lambda: f32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub qualities: Vec<Vec<f64>>
+}
+
+//---------------------------------------------------------------------------------------//
+
+/*
+*   Adaptive operator selection among several local operators by probability matching (Thierens 2005).
+*   Every operator has a quality, the mean relative improvement it achieved recently, and is chosen with
+*   probability p_min + (1 - m * p_min) * quality / (sum of qualities), so no operator is ever ruled out.
+*/
+pub struct AdaptiveLocal {
+    operators: Vec<Box<dyn LocalOperator>>,
+    quality: Vec<f64>,
+    // Weight of the latest reward in the quality
+    pub alpha: f64,
+    pub p_min: f64
+}
+
+impl AdaptiveLocal {
+    pub fn new(operators: Vec<Box<dyn LocalOperator>>) -> AdaptiveLocal {
+        let m = operators.len();
+        AdaptiveLocal {operators, quality: vec![1.0; m], alpha: 0.3, p_min: 0.2 / m as f64}
+    }
+}
+
+impl LocalOperator for AdaptiveLocal {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, x: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight) {
+        let probabilities = self.selection();
+        let mut p = rng.gen::<f64>();
+        let chosen = probabilities.iter().position(|&probability| {
+            p -= probability;
+            p < 0.0
+        }).unwrap_or(self.operators.len() - 1);
+
+        self.operators[chosen].apply(rng, g, x, offspring, k, levy);
+        let reward = if x.tot_conflicts > 0 {
+            x.tot_conflicts.saturating_sub(offspring.tot_conflicts) as f64 / x.tot_conflicts as f64
+        } else {
+            0.0
+        };
+        self.quality[chosen] += self.alpha * (reward - self.quality[chosen]);
+    }
+
+    fn selection(&self) -> Vec<f64> {
+        let m = self.operators.len() as f64;
+        let total: f64 = self.quality.iter().sum();
+        self.quality.iter().map(|&quality| {
+            let share = if total > 0.0 { quality / total } else { 1.0 / m };
+            self.p_min + (1.0 - m * self.p_min) * share
+        }).collect()
+    }
+
+    fn qualities(&self) -> Vec<f64> {
+        self.quality.clone()
+    }
+
+    fn restore_qualities(&mut self, qualities: &[f64]) {
+        if qualities.len() == self.quality.len() {
+            self.quality.copy_from_slice(qualities);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use super::structs::*;
+use super::registry::*;
+use super::levy::*;
+use super::diversity::*;
+use super::pollinators::*;
+use super::*;
+
+/*
+*   Buffers of the partition crossovers, reused between generations
+*/
+#[derive(Default)]
+struct Partitions {
+    // The vertices of every parent ordered by color, the class of color c of parent p
+    // is order[p * n + start[p * (k + 2) + c]..p * n + start[p * (k + 2) + c + 1]]
+    order: Vec<usize>,
+    start: Vec<usize>,
+    next: Vec<usize>,
+    // Number of vertices of every class that are not in the offspring yet
+    sizes: Vec<usize>,
+    assigned: Vec<bool>,
+    recent: Vec<usize>
+}
+
+impl Partitions {
+    /*
+    *   Build the offspring class by class, every step the largest remaining class of any parent
+    *   becomes the next color. A parent that provided one of the last parents / 2 classes is skipped,
+    *   with two parents that alternates between them. Vertices left over get a random color.
+    */
+    fn crossover(&mut self, rng: &mut Rand, g: &Graph, parents: &[&Coloring], offspring: &mut Coloring, k: usize) {
+        let n = g.len();
+        let m = parents.len();
+        debug_assert!(parents.iter().all(|parent| parent.solution.iter().all(|&color| color <= k)), "The parents have colors above k = {}", k);
+        let width = k + 2;
+        self.order.resize(m * n, 0);
+        self.start.clear();
+        self.start.resize(m * width, 0);
+        self.sizes.clear();
+        self.sizes.resize(m * (k + 1), 0);
+        self.assigned.clear();
+        self.assigned.resize(n, false);
+        self.recent.clear();
+
+        // Counting sort of the vertices by color for every parent
+        for (p, parent) in parents.iter().enumerate() {
+            for &color in &parent.solution {
+                self.sizes[p * (k + 1) + color] += 1;
+            }
+            for color in 1..=k {
+                self.start[p * width + color + 1] = self.start[p * width + color] + self.sizes[p * (k + 1) + color];
+            }
+            self.next.clear();
+            self.next.extend_from_slice(&self.start[p * width..p * width + k + 1]);
+            for (v, &color) in parent.solution.iter().enumerate() {
+                self.order[p * n + self.next[color]] = v;
+                self.next[color] += 1;
+            }
+        }
+
+        for color in 1..=k {
+            // Largest class of a parent that did not provide one of the last classes, ties are broken at random
+            let mut chosen = None;
+            let mut largest = 0;
+            let mut ties = 0;
+            for p in (0..m).filter(|p| !self.recent.contains(p)) {
+                for c in 1..=k {
+                    let size = self.sizes[p * (k + 1) + c];
+                    if size > largest {
+                        largest = size;
+                        chosen = Some((p, c));
+                        ties = 1;
+                    } else if size == largest && size > 0 {
+                        ties += 1;
+                        if rng.gen_range(0..ties) == 0 {
+                            chosen = Some((p, c));
+                        }
+                    }
+                }
+            }
+            let Some((p, c)) = chosen else { break };
+            for position in self.start[p * width + c]..self.start[p * width + c + 1] {
+                let v = self.order[p * n + position];
+                if self.assigned[v] {
+                    continue;
+                }
+                self.assigned[v] = true;
+                offspring.solution[v] = color;
+                for (q, parent) in parents.iter().enumerate() {
+                    self.sizes[q * (k + 1) + parent[v]] -= 1;
+                }
+            }
+            self.recent.push(p);
+            if self.recent.len() > m / 2 {
+                self.recent.remove(0);
+            }
+        }
+
+        for v in 0..n {
+            if !self.assigned[v] {
+                offspring.solution[v] = rng.gen_range(1..=k);
+            }
+        }
+        offspring.recompute_conflicts(g);
+    }
+}
+
+/*
+*   Greedy Partition Crossover (GPX, Galinier & Hao 1999) of a flower and the best flower.
+*   Colors are taken as whole classes, so the offspring does not depend on how the parents
+*   number their colors.
+*/
+#[derive(Default)]
+pub struct Gpx {
+    partitions: Partitions
+}
+
+impl GlobalOperator for Gpx {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, parents: &[Coloring], other: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight) {
+        self.partitions.crossover(rng, g, &[other, &parents[0]], offspring, k);
+    }
+}
+
+/*
+*   Multi-parent partition crossover (like AMPaX in MACOL, Lü & Hao 2010) of a flower
+*   and the given number of best flowers of the population
+*/
+pub struct MultiGpx {
+    pub parents: usize,
+    partitions: Partitions
+}
+
+impl MultiGpx {
+    pub fn new(parents: usize) -> MultiGpx {
+        MultiGpx {parents, partitions: Partitions::default()}
+    }
+
+    pub fn standard() -> MultiGpx {
+        MultiGpx::new(3)
+    }
+}
+
+impl GlobalOperator for MultiGpx {
+    fn parents(&self) -> usize {
+        self.parents
+    }
+
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, parents: &[Coloring], other: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight) {
+        let mut all: Vec<&Coloring> = vec![other];
+        all.extend(parents.iter().filter(|parent| parent.index != other.index));
+        self.partitions.crossover(rng, g, &all, offspring, k);
+    }
+}
+
+/*
+*   Like levy_pop1, but the colors of the best flower are first renamed to match the colors of the
+*   flower as well as possible (an optimal assignment on the overlap of their color classes),
+*   so the copied colors mean the same in both
+*/
+#[derive(Default)]
+pub struct AlignedLevy {
+    overlap: Vec<Vec<i64>>,
+    rename: Vec<usize>,
+    sample: Vec<usize>
+}
+
+impl GlobalOperator for AlignedLevy {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, parents: &[Coloring], other: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight) {
+        let best = &parents[0];
+        debug_assert!(best.solution.iter().chain(&other.solution).all(|&color| (1..=k).contains(&color)), "The flowers have colors outside 1..={}", k);
+        // Minimizing the negative overlap maximizes the vertices that keep their color
+        self.overlap.resize(k, Vec::new());
+        for row in self.overlap.iter_mut() {
+            row.clear();
+            row.resize(k, 0);
+        }
+        for (&a, &b) in best.solution.iter().zip(&other.solution) {
+            self.overlap[a - 1][b - 1] -= 1;
+        }
+        self.rename.clear();
+        self.rename.push(0);
+        self.rename.extend(hungarian(&self.overlap).iter().map(|&b| b + 1));
+
+        let n = best.len();
+        let cutoff = levy.sample(rng, n);
+        let indices = sample_nodes(rng, n, cutoff, &mut self.sample);
+        for &i in indices {
+            offspring[i] = self.rename[best[i]];
+        }
+        offspring.update_multiple_conflicts(indices, other, g);
+    }
+}
@@ -0,0 +1,186 @@
+use super::structs::*;
+
+use std::collections::{HashMap, HashSet};
+use rayon::prelude::*;
+
+/*
+*   Solve the assignment problem on a square cost matrix (Hungarian algorithm, O(n^3)).
+*   Returns the column assigned to every row such that the total cost is minimal.
+*/
+pub fn hungarian(cost: &[Vec<i64>]) -> Vec<usize> {
+    let n = cost.len();
+    // Potentials and matching are 1-indexed, column 0 is a virtual starting column
+    let mut u = vec![0; n + 1];
+    let mut v = vec![0; n + 1];
+    let mut row_of = vec![0; n + 1];
+    let mut way = vec![0; n + 1];
+    for row in 1..=n {
+        row_of[0] = row;
+        let mut col = 0;
+        let mut min_to = vec![i64::MAX; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[col] = true;
+            let r = row_of[col];
+            let mut delta = i64::MAX;
+            let mut next = 0;
+            for j in 1..=n {
+                if !used[j] {
+                    let reduced = cost[r - 1][j - 1] - u[r] - v[j];
+                    if reduced < min_to[j] {
+                        min_to[j] = reduced;
+                        way[j] = col;
+                    }
+                    if min_to[j] < delta {
+                        delta = min_to[j];
+                        next = j;
+                    }
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_to[j] -= delta;
+                }
+            }
+            col = next;
+            if row_of[col] == 0 {
+                break;
+            }
+        }
+        // Augment along the alternating path
+        while col != 0 {
+            let prev = way[col];
+            row_of[col] = row_of[prev];
+            col = prev;
+        }
+    }
+    let mut assignment = vec![0; n];
+    for j in 1..=n {
+        if row_of[j] != 0 {
+            assignment[row_of[j] - 1] = j - 1;
+        }
+    }
+    assignment
+}
+
+/*
+*   Count how many nodes every color class of a shares with every color class of b.
+*   Returns the square overlap matrix and the colors of a and b behind its rows and columns.
+*/
+pub fn class_overlap(a: &Coloring, b: &Coloring) -> (Vec<Vec<i64>>, Vec<usize>, Vec<usize>) {
+    let mut colors_a = HashMap::new();
+    let mut colors_b = HashMap::new();
+    let mut pairs = Vec::with_capacity(a.len());
+    for i in 0..a.len() {
+        let next_a = colors_a.len();
+        let next_b = colors_b.len();
+        pairs.push((*colors_a.entry(a[i]).or_insert(next_a), *colors_b.entry(b[i]).or_insert(next_b)));
+    }
+    let size = colors_a.len().max(colors_b.len());
+    let mut overlap = vec![vec![0; size]; size];
+    for (i, j) in pairs {
+        overlap[i][j] += 1;
+    }
+    let mut labels_a = vec![0; size];
+    for (color, i) in colors_a {
+        labels_a[i] = color;
+    }
+    let mut labels_b = vec![0; size];
+    for (color, j) in colors_b {
+        labels_b[j] = color;
+    }
+    (overlap, labels_a, labels_b)
+}
+
+/*
+*   Partition distance between two colorings: the minimum number of nodes that have to change color
+*   to turn one into the other, after optimally matching their color classes.
+*   The distance is invariant to relabelling the colors of either coloring.
+*/
+pub fn partition_distance(a: &Coloring, b: &Coloring) -> usize {
+    let (overlap, _, _) = class_overlap(a, b);
+    let cost: Vec<Vec<i64>> = overlap.iter().map(|row| row.iter().map(|&count| -count).collect()).collect();
+    let matched: i64 = hungarian(&cost).iter().enumerate().map(|(i, &j)| overlap[i][j]).sum();
+    a.len() - matched as usize
+}
+
+/*
+*   Mean partition distance over all pairs of flowers in a population
+*/
+pub fn mean_pairwise_distance(pop: &[Coloring]) -> f64 {
+    let pairs: Vec<(usize, usize)> = (0..pop.len()).flat_map(|i| (i+1..pop.len()).map(move |j| (i, j))).collect();
+    if pairs.is_empty() {
+        return 0.0;
+    }
+    let total: usize = pairs.par_iter().map(|&(i, j)| partition_distance(&pop[i], &pop[j])).sum();
+    total as f64 / pairs.len() as f64
+}
+
+/*
+*   Number of flowers that differ by more than a permutation of their colors
+*/
+pub fn distinct_flowers(pop: &[Coloring]) -> usize {
+    pop.iter().map(|x| x.canonical()).collect::<HashSet<CanonicalColoring>>().len()
+}
+
+/*
+*   Check whether a candidate is at least min_distance away from every flower except the one it replaces
+*/
+pub fn is_diverse(candidate: &Coloring, pop: &[Coloring], replaced: &Coloring, min_distance: usize) -> bool {
+    pop.iter()
+        .filter(|&other| other != replaced)
+        .all(|other| partition_distance(candidate, other) >= min_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coloring(index: usize, solution: &[usize]) -> Coloring {
+        Coloring::from_vec(index, solution.to_vec(), &Graph::from_edges(solution.len(), []))
+    }
+
+    #[test]
+    fn hungarian_finds_the_cheapest_assignment() {
+        let cost = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        assert_eq!(hungarian(&cost), vec![1, 0, 2]);
+        let cost = vec![vec![9, 2, 7, 8], vec![6, 4, 3, 7], vec![5, 8, 1, 8], vec![7, 6, 9, 4]];
+        let assignment = hungarian(&cost);
+        assert_eq!(assignment, vec![1, 0, 2, 3]);
+        assert_eq!(assignment.iter().enumerate().map(|(i, &j)| cost[i][j]).sum::<i64>(), 13);
+        assert!(hungarian(&[]).is_empty());
+    }
+
+    #[test]
+    fn relabelled_colorings_are_at_distance_zero() {
+        let a = coloring(0, &[1, 1, 2, 2, 3, 3, 1, 2]);
+        let b = coloring(1, &[3, 3, 1, 1, 2, 2, 3, 1]);
+        assert_eq!(partition_distance(&a, &b), 0);
+        assert_eq!(partition_distance(&b, &a), 0);
+        assert_eq!(distinct_flowers(&[a, b]), 1);
+    }
+
+    #[test]
+    fn distance_between_different_numbers_of_colors() {
+        let a = coloring(0, &[1, 1, 1, 2, 2, 2]);
+        let b = coloring(1, &[1, 1, 2, 2, 3, 3]);
+        // {0, 1, 2} matches {0, 1} and {3, 4, 5} matches {4, 5}, vertices 2 and 3 change color
+        assert_eq!(partition_distance(&a, &b), 2);
+        assert_eq!(partition_distance(&b, &a), 2);
+        let single = coloring(2, &[1; 6]);
+        assert_eq!(partition_distance(&single, &b), 4);
+        assert_eq!(mean_pairwise_distance(&[a, b, single]), (2 + 3 + 4) as f64 / 3.0);
+    }
+
+    #[test]
+    fn diversity_ignores_the_replaced_flower() {
+        let pop = vec![coloring(0, &[1, 1, 2, 2]), coloring(1, &[1, 2, 1, 2])];
+        let candidate = coloring(0, &[2, 1, 2, 1]);
+        assert!(!is_diverse(&candidate, &pop, &pop[0], 1));
+        assert!(is_diverse(&candidate, &pop, &pop[1], 1));
+        assert!(!is_diverse(&candidate, &pop, &pop[1], 3));
+    }
+}
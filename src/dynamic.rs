@@ -0,0 +1,374 @@
+use super::structs::*;
+use super::greedy::*;
+use super::warm::*;
+use super::*;
+
+use std::fs;
+use std::collections::HashSet;
+use prettytable::*;
+
+/*
+*   Recolor budget of a repair after every batch of updates by default
+*/
+pub const REPAIR_BUDGET: usize = 1000;
+
+/*
+*   A change of a dynamic graph. Vertices are numbered from 0, removing a vertex gives the
+*   last vertex its index, so later updates of the same batch use the new numbering.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Update {
+    // A new vertex with the given neighbors, it gets the index after the last vertex
+    AddVertex(Vec<usize>),
+    RemoveVertex(usize),
+    AddEdge(usize, usize),
+    RemoveEdge(usize, usize)
+}
+
+/*
+*   What a repair did to restore a legal coloring
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Repair {
+    // Conflicts of the coloring before the repair
+    pub conflicts: usize,
+    // Recolor moves of the tabu search
+    pub moves: usize,
+    // Vertices whose color differs from before the repair
+    pub recolored: usize,
+    // Colors added because the budget ran out with conflicts left
+    pub opened: usize
+}
+
+/*
+*   A graph that changes over time together with a coloring of it with colors 1..=k. Every update
+*   keeps the conflicts of the coloring consistent in O(degree), like Coloring::recolor does for a recolor.
+*/
+pub struct DynamicColoring {
+    pub g: Graph,
+    pub x: Coloring,
+    pub k: usize
+}
+
+impl DynamicColoring {
+    pub fn new(g: Graph, x: Coloring) -> DynamicColoring {
+        let k = x.solution.iter().copied().max().unwrap_or(0);
+        DynamicColoring {g, x, k}
+    }
+
+    /*
+    *   Add a vertex connected to neighbors, it gets the color 1..=k used by the fewest of them.
+    *   Returns its index.
+    */
+    pub fn add_vertex(&mut self, neighbors: &[usize]) -> usize {
+        self.k = self.k.max(1);
+        let mut counts = vec![0; self.k + 1];
+        for &neighbor in neighbors {
+            counts[self.x[neighbor]] += 1;
+        }
+        let color = (1..=self.k).min_by_key(|&color| counts[color]).unwrap();
+        let v = self.g.add_vertex();
+        self.x.solution.push(color);
+        self.x.conflicts.push(0);
+        for &neighbor in neighbors {
+            self.add_edge(v, neighbor);
+        }
+        v
+    }
+
+    /*
+    *   Remove vertex v, the last vertex takes its index. Returns the old index of that vertex
+    *   unless v was the last vertex.
+    */
+    pub fn remove_vertex(&mut self, v: usize) -> Option<usize> {
+        for &neighbor in &self.g[v] {
+            if self.x[neighbor] == self.x[v] {
+                self.x.conflicts[neighbor] -= 1;
+            }
+        }
+        self.x.tot_conflicts -= self.x.conflicts[v];
+        self.x.solution.swap_remove(v);
+        self.x.conflicts.swap_remove(v);
+        self.g.remove_vertex(v)
+    }
+
+    /*
+    *   Add the edge between a and b, returns false if it already exists
+    */
+    pub fn add_edge(&mut self, a: usize, b: usize) -> bool {
+        if !self.g.add_edge(a, b) {
+            return false;
+        }
+        if self.x[a] == self.x[b] {
+            self.x.conflicts[a] += 1;
+            self.x.conflicts[b] += 1;
+            self.x.tot_conflicts += 1;
+        }
+        true
+    }
+
+    /*
+    *   Remove the edge between a and b, returns false if there is none
+    */
+    pub fn remove_edge(&mut self, a: usize, b: usize) -> bool {
+        if !self.g.remove_edge(a, b) {
+            return false;
+        }
+        if self.x[a] == self.x[b] {
+            self.x.conflicts[a] -= 1;
+            self.x.conflicts[b] -= 1;
+            self.x.tot_conflicts -= 1;
+        }
+        true
+    }
+
+    /*
+    *   Apply an update, panics if it refers to a vertex that does not exist
+    */
+    pub fn apply(&mut self, update: &Update) {
+        let vertices: &[usize] = match update {
+            Update::AddVertex(neighbors) => neighbors,
+            Update::RemoveVertex(v) => std::slice::from_ref(v),
+            Update::AddEdge(a, b) | Update::RemoveEdge(a, b) => &[*a, *b]
+        };
+        if let Some(v) = vertices.iter().find(|&&v| v >= self.g.len()) {
+            panic!("Invalid update, there is no vertex {} (the graph has {} vertices)", v + 1, self.g.len());
+        }
+        match update {
+            Update::AddVertex(neighbors) => { self.add_vertex(neighbors); }
+            Update::RemoveVertex(v) => { self.remove_vertex(*v); }
+            Update::AddEdge(a, b) => { self.add_edge(*a, *b); }
+            Update::RemoveEdge(a, b) => { self.remove_edge(*a, *b); }
+        }
+    }
+
+    /*
+    *   Apply a batch of updates and repair the coloring afterwards
+    */
+    pub fn update(&mut self, rng: &mut Rand, updates: &[Update], budget: usize) -> Repair {
+        for update in updates {
+            self.apply(update);
+        }
+        self.repair(rng, budget)
+    }
+
+    /*
+    *   Restore a legal coloring while recoloring as few vertices as possible. A tabu search like Tabucol
+    *   with k colors makes at most budget moves, among the moves that remove the most conflicts it prefers
+    *   those that give a vertex its old color back and then those of vertices it already recolored. If
+    *   conflicts are left after that, the conflicting vertices get the smallest color none of their neighbors
+    *   use, which may add colors.
+    */
+    pub fn repair(&mut self, rng: &mut Rand, budget: usize) -> Repair {
+        let DynamicColoring { g, x, k } = self;
+        let n = g.len();
+        let colors = *k + 1;
+        let original = x.solution.clone();
+        let mut repair = Repair {conflicts: x.tot_conflicts, ..Repair::default()};
+        // Iteration until which a color is tabu for a vertex
+        let mut tabu = vec![0; n * colors];
+        let mut counts = vec![0; colors];
+        let mut changes = 0;
+        let mut best = (x.tot_conflicts, 0, x.solution.clone());
+
+        for it in 1..=budget {
+            if x.tot_conflicts == 0 {
+                break;
+            }
+            let mut chosen = None;
+            let mut best_move = (i64::MAX, i64::MAX);
+            let mut ties = 0;
+            let mut critical = 0;
+            for i in (0..n).filter(|&i| x.conflicts[i] > 0) {
+                critical += 1;
+                counts.fill(0);
+                for &neighbor in &g[i] {
+                    counts[x[neighbor]] += 1;
+                }
+                for color in (1..=*k).filter(|&color| color != x[i]) {
+                    let delta = counts[color] as i64 - x.conflicts[i] as i64;
+                    if tabu[i * colors + color] >= it && x.tot_conflicts as i64 + delta >= best.0 as i64 {
+                        continue;
+                    }
+                    let change = if color == original[i] { -1 } else if x[i] == original[i] { 1 } else { 0 };
+                    if (delta, change) < best_move {
+                        best_move = (delta, change);
+                        chosen = Some((i, color));
+                        ties = 1;
+                    } else if (delta, change) == best_move {
+                        // Reservoir sampling over the tied moves
+                        ties += 1;
+                        if rng.gen_range(0..ties) == 0 {
+                            chosen = Some((i, color));
+                        }
+                    }
+                }
+            }
+            let Some((i, color)) = chosen else {
+                break;
+            };
+            tabu[i * colors + x[i]] = it + (0.6 * critical as f64) as usize + rng.gen_range(0..10);
+            x.recolor(i, color, g);
+            changes = (changes as i64 + best_move.1) as usize;
+            repair.moves += 1;
+            if (x.tot_conflicts, changes) < (best.0, best.1) {
+                best = (x.tot_conflicts, changes, x.solution.clone());
+            }
+        }
+        if (x.tot_conflicts, changes) != (best.0, best.1) {
+            x.solution = best.2;
+            x.recompute_conflicts(g);
+        }
+
+        for i in (0..n).sorted_by_key(|&i| std::cmp::Reverse(x.conflicts[i])) {
+            if x.conflicts[i] == 0 {
+                continue;
+            }
+            let neighbor_colors: HashSet<usize> = g[i].iter().map(|&neighbor| x[neighbor]).collect();
+            let color = (1..).find(|color| !neighbor_colors.contains(color)).unwrap();
+            if color > *k {
+                *k = color;
+                repair.opened += 1;
+            }
+            x.recolor(i, color, g);
+        }
+        repair.recolored = x.solution.iter().zip(&original).filter(|(color, old)| color != old).count();
+        repair
+    }
+}
+
+/*
+*   Read batches of updates from a file with one update per line, vertices are numbered from 1 like
+*   in DIMACS files. An empty line ends a batch, lines starting with c are comments.
+*   "a u v" adds the edge {u, v}, "d u v" removes it, "v u..." adds a vertex with neighbors u...
+*   and "r u" removes vertex u.
+*/
+pub fn read_updates(file_name: &str) -> Vec<Vec<Update>> {
+    let content = fs::read_to_string(file_name).unwrap_or_else(|err| panic!("Could not read updates {}: {}", file_name, err));
+    let mut batches = vec![Vec::new()];
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() {
+            if !batches.last().unwrap().is_empty() {
+                batches.push(Vec::new());
+            }
+            continue;
+        }
+        if fields[0] == "c" {
+            continue;
+        }
+        let vertices: Vec<usize> = fields[1..].iter()
+            .map(|field| field.parse::<usize>().ok().filter(|&v| v > 0).unwrap_or_else(|| panic!("Invalid vertex {} in {}", field, file_name)) - 1)
+            .collect();
+        let update = match (fields[0], &vertices[..]) {
+            ("a", &[a, b]) => Update::AddEdge(a, b),
+            ("d", &[a, b]) => Update::RemoveEdge(a, b),
+            ("v", _) => Update::AddVertex(vertices),
+            ("r", &[v]) => Update::RemoveVertex(v),
+            _ => panic!("Invalid update {} in {}", line, file_name)
+        };
+        batches.last_mut().unwrap().push(update);
+    }
+    batches.retain(|batch| !batch.is_empty());
+    batches
+}
+
+/*
+*   Apply batches of updates to a graph and repair its coloring after every batch. The coloring starts
+*   from --init (see solve) or else DSATUR, --out writes the final coloring as colors in vertex order.
+*   Usage: dynamic <graph> <updates> [--init solution] [--budget n] [--seed s] [--out solution]
+*/
+pub fn dynamic_cli(args: &[String]) {
+    let positional = positional_args(args);
+    if positional.len() != 2 {
+        panic!("Usage: dynamic <graph> <updates> [--init solution] [--budget n] [--seed s] [--out solution]");
+    }
+    let graph = Graph::read(&format!("graphs/{}.col", positional[0]));
+    let batches = read_updates(positional[1]);
+    let budget: usize = get_flag(args, "--budget").map_or(REPAIR_BUDGET, |value| value.parse().expect("Invalid budget"));
+    let seed = seed_flag(args);
+    println!("Num nodes: {}", graph.len());
+    println!("Seed: {}", seed);
+
+    let x = match get_flag(args, "--init") {
+        Some(init) => complete(&graph, &read_solution(init, graph.len())).0,
+        None => dsatur2(&graph).1
+    };
+    let mut dynamic = DynamicColoring::new(graph, x);
+    println!("Initial coloring: {} colors", dynamic.k);
+
+    let now = Instant::now();
+    let mut table = table!(["Batch", "Updates", "Nodes", "Edges", "Conflicts", "Moves", "Recolored", "Opened", "Colors"]);
+    for (batch, updates) in batches.iter().enumerate() {
+        let repair = dynamic.update(&mut rng_stream(seed, batch, 0), updates, budget);
+        table.add_row(row![batch + 1, updates.len(), dynamic.g.len(), dynamic.g.num_edges(), repair.conflicts, repair.moves, repair.recolored, repair.opened, dynamic.x.get_k()]);
+    }
+    table.printstd();
+    if let Some(file_name) = get_flag(args, "--out") {
+        fs::write(file_name, dynamic.x.solution.iter().join(" ")).unwrap_or_else(|err| panic!("Could not write solution {}: {}", file_name, err));
+        println!("Coloring written to {}", file_name);
+    }
+    println!("Num colors: {}", dynamic.x.get_k());
+    println!("Repairing {} batches took {:.2} seconds.", batches.len(), now.elapsed().as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_consistent(dynamic: &DynamicColoring) {
+        let DynamicColoring { g, x, .. } = dynamic;
+        assert_eq!(x.len(), g.len());
+        for (i, neighbors) in g.iter().enumerate() {
+            assert!(neighbors.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(neighbors.iter().all(|&neighbor| neighbor != i && g[neighbor].binary_search(&i).is_ok()));
+        }
+        let mut recomputed = x.clone();
+        recomputed.recompute_conflicts(g);
+        assert_eq!(x.conflicts, recomputed.conflicts);
+        assert_eq!(x.tot_conflicts, recomputed.tot_conflicts);
+    }
+
+    fn random_update(rng: &mut Rand, n: usize) -> Update {
+        match rng.gen_range(0..8) {
+            0 if n > 2 => Update::RemoveVertex(rng.gen_range(0..n)),
+            1 => Update::AddVertex((0..rng.gen_range(0..5)).map(|_| rng.gen_range(0..n)).collect()),
+            2..=4 => Update::RemoveEdge(rng.gen_range(0..n), rng.gen_range(0..n)),
+            _ => Update::AddEdge(rng.gen_range(0..n), rng.gen_range(0..n))
+        }
+    }
+
+    #[test]
+    fn random_updates_keep_conflicts_consistent() {
+        for seed in 0..5 {
+            let mut rng = rng_stream(seed, 0, 0);
+            let n = 30;
+            let edges: Vec<(usize, usize)> = (0..90).map(|_| (rng.gen_range(0..n), rng.gen_range(0..n))).collect();
+            let g = Graph::from_edges(n, edges);
+            let x = Coloring::new(0, &g, 4, &mut rng);
+            let mut dynamic = DynamicColoring::new(g, x);
+            assert_consistent(&dynamic);
+            for batch in 0..40 {
+                for _ in 0..rng.gen_range(1..10) {
+                    let update = random_update(&mut rng, dynamic.g.len());
+                    dynamic.apply(&update);
+                    assert_consistent(&dynamic);
+                }
+                dynamic.repair(&mut rng_stream(seed, batch, 1), 100);
+                assert_consistent(&dynamic);
+                assert_eq!(dynamic.x.tot_conflicts, 0);
+                assert!(dynamic.x.solution.iter().all(|&color| (1..=dynamic.k).contains(&color)));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "there is no vertex 4")]
+    fn updates_of_missing_vertices_panic() {
+        let g = Graph::from_edges(3, [(0, 1), (1, 2)]);
+        let x = Coloring::from_vec(0, vec![1, 2, 1], &g);
+        let mut dynamic = DynamicColoring::new(g, x);
+        dynamic.apply(&Update::RemoveVertex(2));
+        dynamic.apply(&Update::AddEdge(0, 3));
+    }
+}
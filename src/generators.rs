@@ -0,0 +1,275 @@
+use super::structs::*;
+use super::*;
+
+use std::collections::HashSet;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+/*
+*   Uniform random graph G(n, p), every edge is present with probability p (DSJC-style)
+*/
+pub fn gnp(rng: &mut StdRng, n: usize, p: f64) -> Graph {
+    let mut edges = Vec::new();
+    for from in 0..n {
+        for to in from+1..n {
+            if rng.gen_bool(p) {
+                edges.push((from, to));
+            }
+        }
+    }
+    Graph::from_edges(n, edges)
+}
+
+/*
+*   Leighton graph with n nodes, chromatic number k and (about) m edges.
+*   The nodes are split into k classes, a k-clique is planted and cliques of size 2..=k over distinct
+*   classes are added until the graph has m edges. The classes form a proper k-coloring.
+*/
+pub fn leighton(rng: &mut StdRng, n: usize, k: usize, m: usize) -> Graph {
+    assert!((2..=n).contains(&k), "Leighton graphs need 2 <= k <= n");
+    let classes = random_classes(rng, n, k);
+    let max_edges = (n * n - classes.iter().map(|class| class.len() * class.len()).sum::<usize>()) / 2;
+    let m = m.min(max_edges);
+
+    let mut edges = HashSet::new();
+    let add_clique = |edges: &mut HashSet<(usize, usize)>, rng: &mut StdRng, size: usize| {
+        let clique: Vec<usize> = index::sample(rng, k, size).iter()
+            .map(|class| *classes[class].choose(rng).unwrap())
+            .collect();
+        for (i, &from) in clique.iter().enumerate() {
+            for &to in &clique[i+1..] {
+                edges.insert((from.min(to), from.max(to)));
+            }
+        }
+    };
+
+    add_clique(&mut edges, rng, k);
+    while edges.len() < m {
+        let size = rng.gen_range(2..=k);
+        add_clique(&mut edges, rng, size);
+    }
+    Graph::from_edges(n, edges)
+}
+
+/*
+*   Flat graph with n nodes and a hidden k-partition. Edges only run between classes, every pair of classes
+*   gets round(p * |A| * |B|) edges, spread so that the degrees towards another class differ by at most one.
+*/
+pub fn flat(rng: &mut StdRng, n: usize, k: usize, p: f64) -> Graph {
+    assert!((1..=n).contains(&k), "Flat graphs need 1 <= k <= n");
+    let classes = random_classes(rng, n, k);
+    let mut edges = Vec::new();
+    for a in 0..k {
+        for b in a+1..k {
+            let (left, right) = (&classes[a], &classes[b]);
+            let m = (p * (left.len() * right.len()) as f64).round() as usize;
+            let left_degrees = balanced_degrees(rng, left.len(), m);
+            let mut capacity = balanced_degrees(rng, right.len(), m);
+            for (&from, degree) in left.iter().zip(left_degrees) {
+                // Connect to the nodes with the most remaining capacity, ties broken randomly
+                let mut order: Vec<usize> = (0..right.len()).collect();
+                order.shuffle(rng);
+                order.sort_by_key(|&j| std::cmp::Reverse(capacity[j]));
+                for &j in order.iter().take(degree) {
+                    capacity[j] -= 1;
+                    edges.push((from, right[j]));
+                }
+            }
+        }
+    }
+    Graph::from_edges(n, edges)
+}
+
+/*
+*   Queen graph of an n x m chessboard, two squares are adjacent if a queen can move between them
+*/
+pub fn queen(n: usize, m: usize) -> Graph {
+    let mut edges = Vec::new();
+    for from in 0..n*m {
+        let (r1, c1) = (from / m, from % m);
+        for to in from+1..n*m {
+            let (r2, c2) = (to / m, to % m);
+            if r1 == r2 || c1 == c2 || r1.abs_diff(r2) == c1.abs_diff(c2) {
+                edges.push((from, to));
+            }
+        }
+    }
+    Graph::from_edges(n * m, edges)
+}
+
+/*
+*   Mycielski graph numbered as in DIMACS, i.e. myciel3 has 11 nodes and chromatic number 4
+*/
+pub fn mycielski(order: usize) -> Graph {
+    insertions(0, order)
+}
+
+/*
+*   k-Insertions graph of the given order (Caramia & Dell'Olmo). Starting from K2, every step adds k + 1
+*   layers of copies of the nodes, where a copy is adjacent to the neighbors of its original in the layer
+*   below, and a root adjacent to the top layer. With k = 0 this is the Mycielski construction.
+*/
+pub fn insertions(k: usize, order: usize) -> Graph {
+    let (n, edges) = (2..=order).fold((2, vec![(0, 1)]), |(n, edges), _| {
+        let mut next = layered_copies(n, &edges, k + 1);
+        let root = n * (k + 2);
+        next.extend((0..n).map(|v| (n * (k + 1) + v, root)));
+        (root + 1, next)
+    });
+    Graph::from_edges(n, edges)
+}
+
+/*
+*   k-FullIns graph of the given order (Caramia & Dell'Olmo). Like k-Insertions, but instead of a single
+*   root a (k + 2)-clique is added where one member is adjacent to every layer of copies and the
+*   others to the top layer.
+*/
+pub fn full_insertions(k: usize, order: usize) -> Graph {
+    let (n, edges) = (2..=order).fold((2, vec![(0, 1)]), |(n, edges), _| {
+        let mut next = layered_copies(n, &edges, k + 1);
+        let clique = n * (k + 2);
+        for layer in 1..=k+1 {
+            next.extend((0..n).map(|v| (layer * n + v, clique + layer)));
+        }
+        next.extend((0..n).map(|v| (n * (k + 1) + v, clique)));
+        for a in 0..k+2 {
+            next.extend((a+1..k+2).map(|b| (clique + a, clique + b)));
+        }
+        (clique + k + 2, next)
+    });
+    Graph::from_edges(n, edges)
+}
+
+/*
+*   Copy a graph into layers 0..=layers where node (v, l + 1) is adjacent to the neighbors of v in layer l
+*/
+fn layered_copies(n: usize, edges: &[(usize, usize)], layers: usize) -> Vec<(usize, usize)> {
+    let mut next = edges.to_vec();
+    for layer in 0..layers {
+        for &(u, v) in edges {
+            next.push((layer * n + u, (layer + 1) * n + v));
+            next.push((layer * n + v, (layer + 1) * n + u));
+        }
+    }
+    next
+}
+
+/*
+*   Randomly split the nodes 0..n into k classes of (almost) equal size
+*/
+fn random_classes(rng: &mut StdRng, n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut nodes: Vec<usize> = (0..n).collect();
+    nodes.shuffle(rng);
+    let mut classes = vec![Vec::new(); k];
+    for (i, v) in nodes.into_iter().enumerate() {
+        classes[i % k].push(v);
+    }
+    classes
+}
+
+/*
+*   Split m edge endpoints over n nodes as evenly as possible
+*/
+fn balanced_degrees(rng: &mut StdRng, n: usize, m: usize) -> Vec<usize> {
+    let mut degrees = vec![m / n; n];
+    for i in index::sample(rng, n, m % n) {
+        degrees[i] += 1;
+    }
+    degrees
+}
+
+/*
+*   Parse the generator arguments, generate the graph and write it in DIMACS format.
+*   Usage: generate <family> <params...> [--seed <seed>] [--out <file>]
+*/
+pub fn generate(args: &[String]) {
+    let positional: Vec<&str> = positional_args(args);
+    let seed = seed_flag(args);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let param = |i: usize| -> &str {
+        positional.get(i).copied().unwrap_or_else(|| panic!("Missing parameter {} for generator {}", i, positional[0]))
+    };
+    let (name, description, graph) = match positional.first().copied() {
+        Some("gnp") => {
+            let (n, p): (usize, f64) = (param(1).parse().unwrap(), param(2).parse().unwrap());
+            (format!("gnp{}_{}_{}", n, p, seed), format!("G(n, p) random graph, n = {}, p = {}", n, p), gnp(&mut rng, n, p))
+        },
+        Some("leighton") => {
+            let (n, k, m): (usize, usize, usize) = (param(1).parse().unwrap(), param(2).parse().unwrap(), param(3).parse().unwrap());
+            (format!("le{}_{}_{}_{}", n, k, m, seed), format!("Leighton graph, n = {}, k = {}, m = {}", n, k, m), leighton(&mut rng, n, k, m))
+        },
+        Some("flat") => {
+            let (n, k, p): (usize, usize, f64) = (param(1).parse().unwrap(), param(2).parse().unwrap(), param(3).parse().unwrap());
+            (format!("flat{}_{}_{}_{}", n, k, p, seed), format!("Flat graph, n = {}, k = {}, p = {}", n, k, p), flat(&mut rng, n, k, p))
+        },
+        Some("queen") => {
+            let (n, m): (usize, usize) = (param(1).parse().unwrap(), param(2).parse().unwrap());
+            (format!("queen{}_{}", n, m), format!("Queen graph of a {} x {} board", n, m), queen(n, m))
+        },
+        Some("myciel") => {
+            let order: usize = param(1).parse().unwrap();
+            (format!("myciel{}", order), format!("Mycielski graph of order {}", order), mycielski(order))
+        },
+        Some("insertions") => {
+            let (k, order): (usize, usize) = (param(1).parse().unwrap(), param(2).parse().unwrap());
+            (format!("{}-Insertions_{}", k, order), format!("{}-Insertions graph of order {}", k, order), insertions(k, order))
+        },
+        Some("fullins") => {
+            let (k, order): (usize, usize) = (param(1).parse().unwrap(), param(2).parse().unwrap());
+            (format!("{}-FullIns_{}", k, order), format!("{}-FullIns graph of order {}", k, order), full_insertions(k, order))
+        },
+        _ => panic!("Unsupported generator! Use one of gnp, leighton, flat, queen, myciel, insertions or fullins")
+    };
+
+    let path = match get_flag(args, "--out") {
+        Some(path) => path.to_string(),
+        None => {
+            std::fs::create_dir_all("graphs/generated").expect("Could not create graphs/generated");
+            format!("graphs/generated/{}.col", name)
+        }
+    };
+    let comments = [
+        format!("FILE: {}", path),
+        format!("DESCRIPTION: {}", description),
+        format!("GENERATOR: {}", positional.join(" ")),
+        format!("SEED: {}", seed),
+    ];
+    graph.write(&path, &comments).expect("Could not write graph");
+    println!("Wrote {} ({} nodes, {} edges) to {}", name, graph.len(), graph.num_edges(), path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(g: &Graph) -> (usize, usize) {
+        (g.len(), g.num_edges())
+    }
+
+    // The node and edge counts of the DIMACS instances
+    #[test]
+    fn mycielski_sizes() {
+        assert_eq!(size(&mycielski(3)), (11, 20));
+        assert_eq!(size(&mycielski(5)), (47, 236));
+    }
+
+    #[test]
+    fn insertions_sizes() {
+        assert_eq!(size(&insertions(1, 4)), (67, 232));
+        assert_eq!(size(&insertions(2, 3)), (37, 72));
+    }
+
+    #[test]
+    fn full_insertions_sizes() {
+        assert_eq!(size(&full_insertions(1, 3)), (30, 100));
+        assert_eq!(size(&full_insertions(3, 3)), (80, 346));
+    }
+
+    // The DIMACS files list every edge of the queen graphs in both directions
+    #[test]
+    fn queen_sizes() {
+        assert_eq!(size(&queen(5, 5)), (25, 160));
+        assert_eq!(size(&queen(8, 8)), (64, 728));
+    }
+}
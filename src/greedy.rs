@@ -2,6 +2,8 @@ use super::structs::*;
 
 use std::collections::{HashSet};
 use itertools::*;
+use rand::Rng;
+use rand::seq::SliceRandom;
 
 pub fn dsatur(g: &Graph) -> (usize, Coloring) {
     let n = g.len();
@@ -85,6 +87,75 @@ pub fn greedy(g: &Graph) -> (usize, Coloring) {
     (k, Coloring::from_vec(0, c, g))
 }
 
+/*
+*   DSATUR limited to k colors with random tie-breaking between the most saturated vertices.
+*   A vertex whose neighbors use all k colors gets the color used by the fewest of them.
+*/
+pub fn random_dsatur(g: &Graph, k: usize, rng: &mut Rand) -> Vec<usize> {
+    let n = g.len();
+    let colors = k + 1;
+    let mut solution = vec![0; n];
+    // Number of neighbors of every vertex with every color
+    let mut counts = vec![0; n * colors];
+    let mut saturations = vec![0; n];
+    for _ in 0..n {
+        let mut chosen = usize::MAX;
+        let mut ties = 0;
+        for v in (0..n).filter(|&v| solution[v] == 0) {
+            if chosen == usize::MAX || saturations[v] > saturations[chosen] {
+                chosen = v;
+                ties = 1;
+            } else if saturations[v] == saturations[chosen] {
+                ties += 1;
+                if rng.gen_range(0..ties) == 0 {
+                    chosen = v;
+                }
+            }
+        }
+        let color = bounded_color(rng, &counts[chosen * colors..(chosen + 1) * colors]);
+        solution[chosen] = color;
+        for &neighbor in &g[chosen] {
+            if counts[neighbor * colors + color] == 0 {
+                saturations[neighbor] += 1;
+            }
+            counts[neighbor * colors + color] += 1;
+        }
+    }
+    solution
+}
+
+/*
+*   Greedy coloring with k colors in a random vertex order, a vertex gets the smallest color
+*   none of its colored neighbors use or else the color used by the fewest of them
+*/
+pub fn random_greedy(g: &Graph, k: usize, rng: &mut Rand) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..g.len()).collect();
+    order.shuffle(rng);
+    let mut solution = vec![0; g.len()];
+    let mut counts = vec![0; k + 1];
+    for &v in &order {
+        counts.fill(0);
+        for &neighbor in &g[v] {
+            counts[solution[neighbor]] += 1;
+        }
+        solution[v] = bounded_color(rng, &counts);
+    }
+    solution
+}
+
+/*
+*   Given how many neighbors use each color 0..=k, the smallest unused color 1..=k,
+*   or else one of the colors used by the fewest neighbors
+*/
+fn bounded_color(rng: &mut Rand, counts: &[usize]) -> usize {
+    if let Some(color) = (1..counts.len()).find(|&color| counts[color] == 0) {
+        return color;
+    }
+    let fewest = counts[1..].iter().min().unwrap();
+    let ties: Vec<usize> = (1..counts.len()).filter(|&color| counts[color] == *fewest).collect();
+    ties[rng.gen_range(0..ties.len())]
+}
+
 fn saturation(g: &Graph, c: &Coloring, i: usize) -> usize {
     let neighbors = &g[i];
     neighbors.iter().map(|&j| c[j]).unique().count()
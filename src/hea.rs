@@ -0,0 +1,123 @@
+use super::structs::*;
+use super::registry::*;
+use super::pollinators::*;
+use super::crossover::*;
+use super::levy::*;
+use super::*;
+
+use serde::{Serialize, Deserialize};
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeaParameters {
+    // Tabucol iterations on every new coloring
+    pub tabu_iterations: usize
+}
+
+impl HeaParameters {
+    pub fn standard() -> HeaParameters {
+        HeaParameters {
+            tabu_iterations: 2000
+        }
+    }
+}
+
+impl Default for HeaParameters {
+    fn default() -> HeaParameters {
+        HeaParameters::standard()
+    }
+}
+
+/*
+*   Stream index reserved for choosing and crossing the parents, the flowers of a new population
+*   are improved with the streams of their index
+*/
+const MATING_STREAM: usize = usize::MAX - 2;
+
+/*
+*   Hybrid Evolutionary Algorithm (Galinier & Hao 1999). Every generation two random parents are
+*   crossed with GPX, the child is improved with Tabucol and replaces the worse parent, unless the
+*   population already contains the same partition.
+*/
+pub struct Hea {
+    settings: HeaParameters,
+    gpx: Gpx,
+    tabucol: Tabucol,
+    levy: LevyFlight,
+    child: Coloring,
+    improved: Coloring
+}
+
+impl Hea {
+    pub fn new(settings: HeaParameters, options: Parameters) -> Hea {
+        Hea {
+            settings,
+            gpx: Gpx::default(),
+            tabucol: Tabucol::new(settings.tabu_iterations),
+            levy: LevyFlight::of(&options),
+            child: Coloring::empty(0, 0),
+            improved: Coloring::empty(0, 0)
+        }
+    }
+}
+
+impl Evolution for Hea {
+    /*
+    *   Random colorings improved with Tabucol
+    */
+    fn populate(&mut self, g: &Graph, n: usize, k: usize, seed: u64, generation: usize) -> Vec<Coloring> {
+        let mut pop = g.populate(&mut rng_stream(seed, generation, POPULATE_STREAM), n, k);
+        let (iterations, levy) = (self.settings.tabu_iterations, &self.levy);
+        pop.par_iter_mut().for_each(|x| {
+            let mut rng = rng_stream(seed, generation, x.index);
+            let mut improved = x.clone();
+            Tabucol::new(iterations).apply(&mut rng, g, x, &mut improved, k, levy);
+            *x = improved;
+        });
+        pop
+    }
+
+    fn generation(&mut self, g: &Graph, pop: &mut [Coloring], best: &Coloring, k: usize, seed: u64, result: &mut RunResult) {
+        let mut rng = rng_stream(seed, result.generations, MATING_STREAM);
+        let a = rng.gen_range(0..pop.len());
+        let b = (a + rng.gen_range(1..pop.len().max(2))) % pop.len();
+
+        self.child.copy_from(&pop[a]);
+        self.gpx.apply(&mut rng, g, std::slice::from_ref(&pop[b]), &pop[a], &mut self.child, k, &self.levy);
+        self.improved.copy_from(&self.child);
+        self.tabucol.apply(&mut rng, g, &self.child, &mut self.improved, k, &self.levy);
+        result.evaluations += 1;
+
+        if pop.iter().any(|x| x.same_partition(&self.improved)) {
+            return;
+        }
+        let worse = if pop[b].tot_conflicts > pop[a].tot_conflicts { b } else { a };
+        self.improved.index = pop[worse].index;
+        self.improved.lifetime = 0;
+        std::mem::swap(&mut pop[worse], &mut self.improved);
+    }
+}
+
+pub fn hea(g: &Graph, k: usize, options: Option<Parameters>, settings: HeaParameters, stop: Option<usize>, budget: Budget, seed: u64) -> RunResult {
+    let options = options.unwrap_or_else(Parameters::standard);
+    descend(g, &mut Hea::new(settings, options), &mut SolverState::new(options.pop_size, k, seed), options, stop, budget, &mut ())
+}
+
+/*
+*   Run HEA on a graph.
+*   Usage: hea <graph> [--tabu-iterations n] [--pop-size n] [--k n] [--seed s]
+*/
+pub fn hea_cli(args: &[String]) {
+    let mut settings = HeaParameters::standard();
+    if let Some(value) = get_flag(args, "--tabu-iterations") {
+        settings.tabu_iterations = value.parse().expect("Invalid number of tabu iterations");
+    }
+    let mut options = Parameters::standard();
+    if let Some(value) = get_flag(args, "--pop-size") {
+        options.pop_size = value.parse().expect("Invalid population size");
+    }
+
+    solver_cli(args, "HEA", 1, || println!("Population: {}, Tabucol iterations: {}", options.pop_size, settings.tabu_iterations), |graph, k, seed| {
+        hea(graph, k, Some(options), settings, None, Budget::standard(), seed)
+    });
+}
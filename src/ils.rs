@@ -0,0 +1,71 @@
+use super::structs::*;
+use super::registry::*;
+use super::levy::*;
+use super::*;
+
+/*
+*   Iterated Local Search on a single coloring. Every generation recolors a Lévy distributed number
+*   of random nodes, improves the result with a local operator until it stops improving and keeps it
+*   if it has at most as many conflicts as the current coloring.
+*/
+pub struct Ils {
+    local: Box<dyn LocalOperator>,
+    levy: LevyFlight,
+    candidate: Coloring,
+    improved: Coloring
+}
+
+impl Ils {
+    pub fn new(local: Box<dyn LocalOperator>, options: Parameters) -> Ils {
+        Ils {local, levy: LevyFlight::of(&options), candidate: Coloring::empty(0, 0), improved: Coloring::empty(0, 0)}
+    }
+}
+
+impl Evolution for Ils {
+    fn generation(&mut self, g: &Graph, pop: &mut [Coloring], best: &Coloring, k: usize, seed: u64, result: &mut RunResult) {
+        let mut rng = rng_stream(seed, result.generations, 0);
+        let x = &mut pop[0];
+        let n = x.len();
+
+        // Perturbation
+        self.candidate.copy_from(x);
+        let strength = self.levy.sample(&mut rng, n).max(1);
+        for _ in 0..strength {
+            let i = rng.gen_range(0..n);
+            self.candidate.recolor(i, rng.gen_range(1..=k), g);
+        }
+
+        // Local search, a legal coloring cannot be improved
+        while self.candidate.tot_conflicts > 0 {
+            self.improved.copy_from(&self.candidate);
+            self.local.apply(&mut rng, g, &self.candidate, &mut self.improved, k, &self.levy);
+            result.evaluations += 1;
+            if self.improved.tot_conflicts >= self.candidate.tot_conflicts {
+                break;
+            }
+            std::mem::swap(&mut self.candidate, &mut self.improved);
+        }
+
+        // Acceptance
+        if self.candidate.tot_conflicts <= x.tot_conflicts {
+            std::mem::swap(x, &mut self.candidate);
+        }
+    }
+}
+
+pub fn iterated_local_search(g: &Graph, local: &str, k: usize, options: Option<Parameters>, stop: Option<usize>, budget: Budget, seed: u64) -> RunResult {
+    let options = options.unwrap_or_else(Parameters::standard);
+    let local = registry().read().unwrap().local(local).unwrap_or_else(|err| panic!("{}", err))();
+    descend(g, &mut Ils::new(local, options), &mut SolverState::new(1, k, seed), options, stop, budget, &mut ())
+}
+
+/*
+*   Run Iterated Local Search on a graph.
+*   Usage: ils <graph> [--local name] [--k n] [--seed s]
+*/
+pub fn ils_cli(args: &[String]) {
+    let local = get_flag(args, "--local").unwrap_or("tabucol");
+    solver_cli(args, "ILS", 1, || println!("Local search: {}", local), |graph, k, seed| {
+        iterated_local_search(graph, local, k, None, None, Budget::standard(), seed)
+    });
+}
@@ -0,0 +1,233 @@
+use super::structs::*;
+use super::greedy::*;
+use super::*;
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use itertools::Itertools;
+use prettytable::*;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    pub density: f64,
+    pub min_degree: usize,
+    pub avg_degree: f64,
+    pub max_degree: usize,
+    pub degree_histogram: BTreeMap<usize, usize>,
+    pub degeneracy: usize,
+    pub core_numbers: Vec<usize>,
+    pub core_sizes: BTreeMap<usize, usize>,
+    pub components: usize,
+    pub clique_lower_bound: usize,
+    pub dsatur_upper_bound: usize
+}
+
+impl GraphStats {
+    /*
+    *   Calculate the statistics of a full graph
+    */
+    pub fn compute(g: &Graph) -> GraphStats {
+        let n = g.len();
+        let edges = g.num_edges();
+        let degrees: Vec<usize> = g.iter().map(|neighbors| neighbors.len()).collect();
+        let core_numbers = core_decomposition(g);
+
+        GraphStats {
+            nodes: n,
+            edges,
+            density: if n > 1 { 2.0 * edges as f64 / (n * (n - 1)) as f64 } else { 0.0 },
+            min_degree: degrees.iter().copied().min().unwrap_or(0),
+            avg_degree: if n > 0 { 2.0 * edges as f64 / n as f64 } else { 0.0 },
+            max_degree: degrees.iter().copied().max().unwrap_or(0),
+            degree_histogram: degrees.iter().copied().counts().into_iter().collect(),
+            degeneracy: core_numbers.iter().copied().max().unwrap_or(0),
+            core_sizes: core_numbers.iter().copied().counts().into_iter().collect(),
+            components: components(g),
+            clique_lower_bound: greedy_clique(g, &core_numbers).len(),
+            dsatur_upper_bound: if n > 0 { dsatur2(g).0 } else { 0 },
+            core_numbers
+        }
+    }
+
+    /*
+    *   Print a human-readable report
+    */
+    pub fn print(&self) {
+        println!("Num nodes: {}", self.nodes);
+        println!("Num edges: {}", self.edges);
+        println!("Density: {:.4}", self.density);
+        println!("Degree (min/avg/max): {}/{:.2}/{}", self.min_degree, self.avg_degree, self.max_degree);
+        println!("Degeneracy: {}", self.degeneracy);
+        println!("Connected components: {}", self.components);
+        println!("Chromatic number bounds: {} <= k* <= {}", self.clique_lower_bound, self.dsatur_upper_bound);
+
+        let mut table = table!(["Degree", "Nodes"]);
+        for (degree, count) in &self.degree_histogram {
+            table.add_row(row![degree, count]);
+        }
+        table.printstd();
+
+        let mut table = table!(["Core number", "Nodes"]);
+        for (core, count) in &self.core_sizes {
+            table.add_row(row![core, count]);
+        }
+        table.printstd();
+    }
+}
+
+/*
+*   Calculate the core number of every node by repeatedly removing a node of minimum degree (Batagelj-Zaversnik)
+*/
+pub fn core_decomposition(g: &Graph) -> Vec<usize> {
+    let n = g.len();
+    let mut degree: Vec<usize> = g.iter().map(|neighbors| neighbors.len()).collect();
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+
+    // Nodes sorted by degree, with the start of every degree bucket
+    let mut bucket_start = vec![0; max_degree + 2];
+    for &d in &degree {
+        bucket_start[d + 1] += 1;
+    }
+    for d in 1..bucket_start.len() {
+        bucket_start[d] += bucket_start[d - 1];
+    }
+    let mut order = vec![0; n];
+    let mut position = vec![0; n];
+    let mut next = bucket_start.clone();
+    for v in 0..n {
+        position[v] = next[degree[v]];
+        order[position[v]] = v;
+        next[degree[v]] += 1;
+    }
+
+    for i in 0..n {
+        let v = order[i];
+        for &u in &g[v] {
+            if degree[u] > degree[v] {
+                // Move u to the front of its bucket and shrink the bucket
+                let du = degree[u];
+                let w = order[bucket_start[du]];
+                if u != w {
+                    order.swap(position[u], position[w]);
+                    position.swap(u, w);
+                }
+                bucket_start[du] += 1;
+                degree[u] -= 1;
+            }
+        }
+    }
+    degree
+}
+
+/*
+*   Count the connected components with a breadth first search
+*/
+pub fn components(g: &Graph) -> usize {
+    let mut visited = vec![false; g.len()];
+    let mut count = 0;
+    for start in 0..g.len() {
+        if visited[start] {
+            continue;
+        }
+        count += 1;
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some(v) = queue.pop_front() {
+            for &u in &g[v] {
+                if !visited[u] {
+                    visited[u] = true;
+                    queue.push_back(u);
+                }
+            }
+        }
+    }
+    count
+}
+
+/*
+*   Greedily grow a clique from every node, preferring neighbors with high core numbers.
+*   The largest clique found is a lower bound on the chromatic number.
+*/
+pub fn greedy_clique(g: &Graph, core_numbers: &[usize]) -> Vec<usize> {
+    let adjacent: Vec<HashSet<usize>> = g.iter().map(|neighbors| neighbors.iter().copied().collect()).collect();
+    let mut best = Vec::new();
+    for v in 0..g.len() {
+        // A clique through v can not be larger than core(v) + 1
+        if core_numbers[v] < best.len() {
+            continue;
+        }
+        let candidates = g[v].iter().copied().sorted_unstable_by_key(|&u| std::cmp::Reverse(core_numbers[u]));
+        let mut clique = vec![v];
+        for u in candidates {
+            if clique.iter().all(|w| adjacent[u].contains(w)) {
+                clique.push(u);
+            }
+        }
+        if clique.len() > best.len() {
+            best = clique;
+        }
+    }
+    best
+}
+
+/*
+*   Print the statistics of a graph.
+*   Usage: info <graph> [--json]
+*/
+pub fn info(args: &[String]) {
+    let positional = positional_args(args);
+    let name = positional.first().expect("Missing graph name");
+    let graph = Graph::read(&format!("graphs/{}.col", name));
+    let stats = GraphStats::compute(&graph);
+    if args.iter().any(|arg| arg == "--json") {
+        println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+    } else {
+        println!("Graph: {}", name);
+        stats.print();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A triangle 0, 1, 2 with the pendant vertex 3 at 0
+    fn triangle_with_pendant() -> Graph {
+        Graph::from_edges(4, [(0, 1), (1, 2), (0, 2), (0, 3)])
+    }
+
+    #[test]
+    fn triangle_with_pendant_stats() {
+        let g = triangle_with_pendant();
+        let cores = core_decomposition(&g);
+        assert_eq!(cores, vec![2, 2, 2, 1]);
+        assert_eq!(components(&g), 1);
+        assert_eq!(greedy_clique(&g, &cores).iter().copied().sorted().collect::<Vec<usize>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn odd_cycle_stats() {
+        let g = Graph::from_edges(5, (0..5).map(|i| (i, (i + 1) % 5)));
+        let cores = core_decomposition(&g);
+        assert_eq!(cores, vec![2; 5]);
+        assert_eq!(greedy_clique(&g, &cores).len(), 2);
+        let stats = GraphStats::compute(&g);
+        assert_eq!((stats.clique_lower_bound, stats.dsatur_upper_bound), (2, 3));
+    }
+
+    #[test]
+    fn separate_components() {
+        // The triangle with its pendant vertex, a K4 on 4..8 and the isolated vertex 8
+        let k4 = (4..8).flat_map(|a| (a + 1..8).map(move |b| (a, b)));
+        let g = Graph::from_edges(9, [(0, 1), (1, 2), (0, 2), (0, 3)].into_iter().chain(k4));
+        assert_eq!(components(&g), 3);
+        assert_eq!(core_decomposition(&g), vec![2, 2, 2, 1, 3, 3, 3, 3, 0]);
+        let stats = GraphStats::compute(&g);
+        assert_eq!(stats.degeneracy, 3);
+        assert_eq!(stats.components, 3);
+        assert_eq!(stats.clique_lower_bound, 4);
+        assert_eq!(stats.core_sizes, BTreeMap::from([(0, 1), (1, 1), (2, 3), (3, 4)]));
+    }
+}
@@ -0,0 +1,215 @@
+use super::structs::*;
+use super::greedy::*;
+use super::registry::*;
+use super::*;
+
+use std::str::FromStr;
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize};
+
+/*
+*   Which islands receive the elite flowers of an island during migration
+*/
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Topology {
+    Ring,
+    Full,
+    Random
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Topology, String> {
+        match s {
+            "ring" => Ok(Topology::Ring),
+            "full" => Ok(Topology::Full),
+            "random" => Ok(Topology::Random),
+            _ => Err(format!("Unknown topology {}, use ring, full or random", s))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IslandParameters {
+    pub islands: usize,
+    pub island_size: usize,
+    pub migration_interval: usize,
+    pub migrants: usize,
+    pub topology: Topology
+}
+
+impl IslandParameters {
+    pub fn standard() -> IslandParameters {
+        IslandParameters {
+            islands: 4,
+            island_size: POP_SIZE,
+            migration_interval: 50,
+            migrants: 2,
+            topology: Topology::Ring
+        }
+    }
+}
+
+impl Default for IslandParameters {
+    fn default() -> IslandParameters {
+        IslandParameters::standard()
+    }
+}
+
+/*
+*   Stream index reserved for the random choices made during migration
+*/
+const MIGRATE_STREAM: usize = usize::MAX - 1;
+
+/*
+*   Island model DFPA. Every island is a separate population evolved on its own rayon task, every
+*   migration_interval generations the best flowers of each island replace the worst flowers of its
+*   neighbors. As soon as one island finds a legal coloring all islands continue with k - 1.
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn island_fpa(g: &Graph, pollinator: &Pollinator, k: usize, options: Option<Parameters>, islands: IslandParameters, stop: Option<usize>, budget: Budget, seed: u64) -> RunResult {
+    let options = options.unwrap_or_else(Parameters::standard);
+    let start = Instant::now();
+    let mut result = RunResult::new(k + 1);
+    let mut k = k;
+    let levy = LevyFlight::of(&options);
+    let parent_count = pollinator.context().global.parents().max(options.restart.parents());
+    let mut contexts: Vec<Vec<FlowerContext>> = (0..islands.islands).map(|_| pollinator.contexts(islands.island_size)).collect();
+    'descent: loop {
+        if k < stop.unwrap_or(1) {
+            break;
+        }
+        let mut pops = populate_islands(g, &mut rng_stream(seed, result.generations, POPULATE_STREAM), k, islands);
+        for it in 0..budget.max_gen {
+            if budget.is_exceeded(start.elapsed().as_secs_f64()) {
+                break;
+            }
+            let generation = result.generations;
+            let (found, restarts) = pops.par_iter_mut().zip(contexts.par_iter_mut()).enumerate().map(|(i, (pop, contexts))| {
+                let parents = best_flowers(pop, parent_count);
+                if parents[0].tot_conflicts == 0 {
+                    return (true, RestartCounts::default());
+                }
+                let mut rng = rng_stream(seed, generation, i);
+                let prev = if options.min_distance > 0 { pop.clone() } else { Vec::new() };
+                let restarts = pop.iter_mut().zip(contexts.iter_mut()).map(|(x, context)| RestartCounts::of(pollinate(context, &mut rng, g, x, &parents, &prev, k, options, &levy)))
+                    .fold(RestartCounts::default(), |a, b| a + b);
+                (false, restarts)
+            }).reduce(|| (false, RestartCounts::default()), |a, b| (a.0 || b.0, a.1 + b.1));
+
+            if found {
+                result.k = k;
+                result.time_to_best = start.elapsed().as_secs_f64();
+                k -= 1;
+                continue 'descent;
+            }
+            result.generations += 1;
+            result.evaluations += islands.islands * islands.island_size;
+            result.restarts += restarts.total();
+            result.count_restarts(restarts);
+            if (it + 1) % islands.migration_interval.max(1) == 0 {
+                migrate(&mut pops, islands, &mut rng_stream(seed, generation, MIGRATE_STREAM));
+            }
+        }
+        break;
+    }
+    result.total_time = start.elapsed().as_secs_f64();
+    result
+}
+
+/*
+*   Initialize every island with random colorings, flower indices are unique over all islands
+*/
+fn populate_islands(g: &Graph, rng: &mut Rand, k: usize, islands: IslandParameters) -> Vec<Vec<Coloring>> {
+    (0..islands.islands).map(|i| {
+        let mut pop = g.populate(rng, islands.island_size, k);
+        for x in pop.iter_mut() {
+            x.index += i * islands.island_size;
+        }
+        pop
+    }).collect()
+}
+
+/*
+*   Send copies of the best flowers of every island to its neighbors, where they replace the worst flowers.
+*   The best flower of the receiving island is never replaced.
+*/
+fn migrate(pops: &mut [Vec<Coloring>], islands: IslandParameters, rng: &mut Rand) {
+    let num_islands = pops.len();
+    if num_islands < 2 || islands.migrants == 0 {
+        return;
+    }
+    let emigrants: Vec<Vec<Coloring>> = pops.iter()
+        .map(|pop| pop.iter().sorted_by_key(|x| x.tot_conflicts).take(islands.migrants).cloned().collect())
+        .collect();
+
+    let mut incoming: Vec<Vec<Coloring>> = vec![Vec::new(); num_islands];
+    for (from, elites) in emigrants.into_iter().enumerate() {
+        let targets: Vec<usize> = match islands.topology {
+            Topology::Ring => vec![(from + 1) % num_islands],
+            Topology::Full => (0..num_islands).filter(|&to| to != from).collect(),
+            Topology::Random => vec![*(0..num_islands).filter(|&to| to != from).collect::<Vec<usize>>().choose(rng).unwrap()]
+        };
+        for to in targets {
+            incoming[to].extend(elites.iter().cloned());
+        }
+    }
+
+    for (pop, mut immigrants) in pops.iter_mut().zip(incoming) {
+        immigrants.sort_by_key(|x| x.tot_conflicts);
+        // The worst flowers are replaced in place, the contexts of the flowers stay with them
+        let worst: Vec<usize> = (0..pop.len()).sorted_by_key(|&i| std::cmp::Reverse(pop[i].tot_conflicts)).collect();
+        let replace = immigrants.len().min(pop.len() - 1);
+        for (i, immigrant) in worst.into_iter().zip(immigrants).take(replace) {
+            let x = &mut pop[i];
+            if immigrant.tot_conflicts < x.tot_conflicts {
+                *x = Coloring { index: x.index, lifetime: 0, ..immigrant };
+            }
+        }
+    }
+}
+
+/*
+*   Run the island model on a graph.
+*   Usage: island <graph> [--pollinator name | --global name --local name] [--islands n] [--size n] [--interval m] [--migrants e] [--topology ring|full|random] [--seed s]
+*/
+pub fn island(args: &[String]) {
+    let positional = positional_args(args);
+    let name = positional.first().expect("Missing graph name");
+    let mut islands = IslandParameters::standard();
+    if let Some(value) = get_flag(args, "--islands") {
+        islands.islands = value.parse().expect("Invalid number of islands");
+    }
+    if let Some(value) = get_flag(args, "--size") {
+        islands.island_size = value.parse().expect("Invalid island size");
+    }
+    if let Some(value) = get_flag(args, "--interval") {
+        islands.migration_interval = value.parse().expect("Invalid migration interval");
+    }
+    if let Some(value) = get_flag(args, "--migrants") {
+        islands.migrants = value.parse().expect("Invalid number of migrants");
+    }
+    if let Some(value) = get_flag(args, "--topology") {
+        islands.topology = value.parse().unwrap();
+    }
+
+    let graph = Graph::read(&format!("graphs/{}.col", name));
+    println!("Num nodes: {}", graph.len());
+    println!("Islands: {} x {}, migration every {} generations ({:?})", islands.islands, islands.island_size, islands.migration_interval, islands.topology);
+
+    let seed = seed_flag(args);
+    println!("Seed: {}", seed);
+
+    let now = Instant::now();
+    let (greedy, _greedy_sol) = dsatur2(&graph);
+    let pollinator = Pollinator::by_name(&pollinator_name(args)).unwrap_or_else(|err| panic!("{}", err));
+    let result = island_fpa(&graph, &pollinator, greedy-1, None, islands, None, Budget::standard(), seed);
+    let elapsed_time = now.elapsed();
+
+    println!("Num colors: {}", result.k);
+    println!("Running island DFPA took {} seconds.", elapsed_time.as_secs());
+}
@@ -0,0 +1,365 @@
+use super::statistics::*;
+use super::*;
+
+use std::collections::HashMap;
+use std::f64::consts::{PI, SQRT_2};
+use std::sync::{Arc, Mutex, OnceLock};
+use rand::SeedableRng;
+use prettytable::*;
+
+// The step length distribution is tabulated for steps from 10^MIN_LOG_STEP to 10^MAX_LOG_STEP
+const TABLE_SIZE: usize = 1024;
+const MIN_LOG_STEP: f64 = -8.0;
+const MAX_LOG_STEP: f64 = 12.0;
+
+/*
+*   Standard deviation of the numerator in Mantegna's algorithm
+*/
+pub fn mantegna_sigma(beta: f64) -> f64 {
+    let numerator = gamma(1.0 + beta) * (PI * beta / 2.0).sin();
+    let denominator = gamma((1.0 + beta) / 2.0) * beta * 2f64.powf((beta - 1.0) / 2.0);
+    (numerator / denominator).powf(1.0 / beta)
+}
+
+/*
+*   A Lévy flight step by Mantegna's algorithm (Mantegna 1994, as used in FPA by Yang 2012):
+*   u / |v|^(1/beta) with u ~ N(0, sigma^2) and v ~ N(0, 1). With beta = 2 the steps are N(0, 1).
+*/
+pub fn mantegna(rng: &mut Rand, beta: f64) -> f64 {
+    let u: f64 = rng.sample(StandardNormal);
+    if beta >= 2.0 {
+        return u;
+    }
+    let v: f64 = rng.sample(StandardNormal);
+    u * mantegna_sigma(beta) / v.abs().powf(1.0 / beta)
+}
+
+/*
+*   A step of the Lévy distribution c / N^2 with N ~ N(0, 1), the sampler of the original DFPA
+*/
+pub fn levy(rng: &mut Rand, c: f64) -> f64 {
+    let n: f64 = rng.sample(StandardNormal);
+    c / (n * n)
+}
+
+/*
+*   P(|step| <= s) for Mantegna's algorithm, which is E_v[erf(s |v|^(1/beta) / (sigma sqrt(2)))]
+*   and integrated with Simpson's rule over t = sqrt(v), since |v|^(1/beta) has a cusp at 0 that
+*   Simpson's rule misses in the tail. Without beta it is P(1 / N^2 <= s) = erfc(1 / sqrt(2 s)).
+*/
+fn step_cdf(beta: Option<f64>, s: f64) -> f64 {
+    let Some(beta) = beta else {
+        return erfc(1.0 / (2.0 * s).sqrt());
+    };
+    if beta >= 2.0 {
+        return 1.0 - erfc(s / SQRT_2);
+    }
+    const INTERVALS: usize = 2000;
+    const T_MAX: f64 = 3.0;
+    let scale = s / (mantegna_sigma(beta) * SQRT_2);
+    let h = T_MAX / INTERVALS as f64;
+    // v = t^2 and dv = 2t dt
+    let f = |t: f64| 2.0 * t * (-t.powi(4) / 2.0).exp() * (1.0 - erfc(scale * t.powf(2.0 / beta)));
+    let mut sum = f(0.0) + f(T_MAX);
+    for i in 1..INTERVALS {
+        sum += if i % 2 == 1 { 4.0 } else { 2.0 } * f(i as f64 * h);
+    }
+    // Twice the integral over positive v of the standard normal density
+    (2.0 / (2.0 * PI).sqrt() * sum * h / 3.0).min(1.0)
+}
+
+/*
+*   The distribution of the step length |u| / |v|^(1/beta), or 1 / N^2 without beta, tabulated on a
+*   logarithmic grid. Values in between are interpolated linearly in log(s), for the distribution as well as its inverse.
+*/
+pub struct LevyTable {
+    pub beta: Option<f64>,
+    cdf: Vec<f64>
+}
+
+impl LevyTable {
+    pub fn new(beta: Option<f64>) -> LevyTable {
+        if let Some(beta) = beta {
+            assert!(beta > 0.0 && beta <= 2.0, "The stability index beta must be in (0, 2], got {}", beta);
+        }
+        let mut cdf: Vec<f64> = (0..TABLE_SIZE).map(|i| step_cdf(beta, LevyTable::grid(i as f64))).collect();
+        // Keep the numerical integration from breaking monotonicity
+        for i in 1..TABLE_SIZE {
+            cdf[i] = cdf[i].max(cdf[i - 1]);
+        }
+        LevyTable {beta, cdf}
+    }
+
+    fn grid(position: f64) -> f64 {
+        10f64.powf(MIN_LOG_STEP + position * (MAX_LOG_STEP - MIN_LOG_STEP) / (TABLE_SIZE - 1) as f64)
+    }
+
+    /*
+    *   P(|step| <= s), below the grid the distribution is taken to be linear from 0
+    */
+    pub fn cdf(&self, s: f64) -> f64 {
+        if s <= 0.0 {
+            return 0.0;
+        }
+        let position = (s.log10() - MIN_LOG_STEP) / (MAX_LOG_STEP - MIN_LOG_STEP) * (TABLE_SIZE - 1) as f64;
+        if position <= 0.0 {
+            return self.cdf[0] * s / LevyTable::grid(0.0);
+        } else if position >= (TABLE_SIZE - 1) as f64 {
+            return self.cdf[TABLE_SIZE - 1];
+        }
+        let i = position as usize;
+        self.cdf[i] + (position - i as f64) * (self.cdf[i + 1] - self.cdf[i])
+    }
+
+    /*
+    *   The step length s with cdf(s) = p
+    */
+    pub fn quantile(&self, p: f64) -> f64 {
+        if p <= self.cdf[0] {
+            return if self.cdf[0] > 0.0 { LevyTable::grid(0.0) * p / self.cdf[0] } else { 0.0 };
+        }
+        let i = self.cdf.partition_point(|&c| c < p);
+        if i >= TABLE_SIZE {
+            return LevyTable::grid((TABLE_SIZE - 1) as f64);
+        }
+        let (low, high) = (self.cdf[i - 1], self.cdf[i]);
+        let fraction = if high > low { (p - low) / (high - low) } else { 0.0 };
+        LevyTable::grid(i as f64 - 1.0 + fraction)
+    }
+}
+
+/*
+*   The table for a stability index, every table is computed once per process
+*/
+pub fn levy_table(beta: Option<f32>) -> Arc<LevyTable> {
+    static TABLES: OnceLock<Mutex<HashMap<Option<u32>, Arc<LevyTable>>>> = OnceLock::new();
+    let mut tables = TABLES.get_or_init(Default::default).lock().unwrap();
+    tables.entry(beta.map(f32::to_bits)).or_insert_with(|| Arc::new(LevyTable::new(beta.map(f64::from)))).clone()
+}
+
+/*
+*   Draws the number of nodes the operators change: the length of a Lévy flight step with
+*   stability index beta (or of c / N^2 without beta), scaled by lambda and rounded
+*/
+#[derive(Clone)]
+pub struct LevyFlight {
+    pub lambda: f32,
+    pub beta: Option<f32>,
+    table: Arc<LevyTable>
+}
+
+impl LevyFlight {
+    pub fn new(lambda: f32, beta: Option<f32>) -> LevyFlight {
+        LevyFlight {lambda, beta, table: levy_table(beta)}
+    }
+
+    pub fn of(options: &Parameters) -> LevyFlight {
+        LevyFlight::new(options.lambda, options.beta)
+    }
+
+    /*
+    *   A step below limit. The uniform number is drawn from the part of the distribution below
+    *   limit and transformed with the inverse distribution, so unlike rejection sampling every
+    *   step takes the same time, however small limit is.
+    */
+    pub fn sample(&self, rng: &mut Rand, limit: usize) -> usize {
+        if limit <= 1 {
+            return 0;
+        }
+        let truncation = self.table.cdf((limit as f64 - 0.5) / self.lambda as f64);
+        let p = rng.gen::<f64>() * truncation;
+        ((self.table.quantile(p) * self.lambda as f64).round() as usize).min(limit - 1)
+    }
+
+    /*
+    *   The probability of every step below limit
+    */
+    pub fn probabilities(&self, limit: usize) -> Vec<f64> {
+        let lambda = self.lambda as f64;
+        let truncation = self.table.cdf((limit as f64 - 0.5) / lambda);
+        (0..limit).map(|i| {
+            let low = if i == 0 { 0.0 } else { self.table.cdf((i as f64 - 0.5) / lambda) };
+            (self.table.cdf((i as f64 + 0.5) / lambda) - low) / truncation
+        }).collect()
+    }
+}
+
+/*
+*   Pool neighboring bins until every bin has at least min_total in the reference,
+*   a too small last bin is added to the one before
+*/
+fn pool(reference: &[f64], observed: &[f64], min_total: f64) -> (Vec<f64>, Vec<f64>) {
+    let (mut pooled_reference, mut pooled_observed) = (Vec::new(), Vec::new());
+    let (mut r, mut o) = (0.0, 0.0);
+    for (a, b) in reference.iter().zip(observed) {
+        r += a;
+        o += b;
+        if r >= min_total {
+            pooled_reference.push(r);
+            pooled_observed.push(o);
+            r = 0.0;
+            o = 0.0;
+        }
+    }
+    if r > 0.0 || o > 0.0 {
+        if pooled_reference.is_empty() {
+            pooled_reference.push(0.0);
+            pooled_observed.push(0.0);
+        }
+        *pooled_reference.last_mut().unwrap() += r;
+        *pooled_observed.last_mut().unwrap() += o;
+    }
+    (pooled_reference, pooled_observed)
+}
+
+/*
+*   Chi-squared goodness of fit of observed counts against expected counts, (statistic, df, p)
+*/
+fn goodness_of_fit(expected: &[f64], observed: &[f64]) -> (f64, usize, f64) {
+    let (expected, observed) = pool(expected, observed, 5.0);
+    let statistic: f64 = expected.iter().zip(&observed).map(|(e, o)| (o - e) * (o - e) / e).sum();
+    let df = expected.len().saturating_sub(1).max(1);
+    (statistic, df, chi_squared_sf(statistic, df as f64))
+}
+
+/*
+*   Chi-squared test that two samples of the same size come from the same distribution, (statistic, df, p)
+*/
+fn homogeneity(a: &[f64], b: &[f64]) -> (f64, usize, f64) {
+    let total: Vec<f64> = a.iter().zip(b).map(|(x, y)| x + y).collect();
+    let (pooled_total, pooled_a) = pool(&total, a, 10.0);
+    let statistic: f64 = pooled_total.iter().zip(&pooled_a).map(|(t, x)| {
+        let y = t - x;
+        (x - y) * (x - y) / t
+    }).sum();
+    let df = pooled_total.len().saturating_sub(1).max(1);
+    (statistic, df, chi_squared_sf(statistic, df as f64))
+}
+
+/*
+*   The result of checking the step sampler for one beta, the tests are (statistic, df, p)
+*/
+pub struct StepCheck {
+    pub probabilities: Vec<f64>,
+    // Steps drawn by the truncated inverse CDF against the tabulated distribution
+    pub fit: (f64, usize, f64),
+    // Steps drawn by the truncated inverse CDF against steps drawn directly and rejected at the limit
+    pub direct: (f64, usize, f64)
+}
+
+/*
+*   Check the step sampler statistically for a stability index, or for c / N^2 without beta: samples
+*   steps below limit by the truncated inverse CDF and directly (with Mantegna's algorithm or as c / N^2)
+*/
+pub fn check_steps(lambda: f32, beta: Option<f32>, limit: usize, samples: usize, seed: u64) -> StepCheck {
+    let flight = LevyFlight::new(lambda, beta);
+    let probabilities = flight.probabilities(limit);
+    let mut rng = Rand::seed_from_u64(seed);
+
+    let mut inverse = vec![0.0; limit];
+    for _ in 0..samples {
+        inverse[flight.sample(&mut rng, limit)] += 1.0;
+    }
+    let mut direct = vec![0.0; limit];
+    for _ in 0..samples {
+        let step = loop {
+            let step = match beta {
+                Some(beta) => lambda as f64 * mantegna(&mut rng, beta as f64).abs(),
+                None => levy(&mut rng, lambda as f64)
+            }.round();
+            if step < limit as f64 {
+                break step as usize;
+            }
+        };
+        direct[step] += 1.0;
+    }
+
+    let expected: Vec<f64> = probabilities.iter().map(|p| p * samples as f64).collect();
+    let fit = goodness_of_fit(&expected, &inverse);
+    let direct = homogeneity(&inverse, &direct);
+    StepCheck {probabilities, fit, direct}
+}
+
+/*
+*   Check the step sampler statistically for every beta, and for c / N^2 without beta, see check_steps.
+*   The run fails if any test rejects at a family-wise significance level of 0.01.
+*   Usage: levy [--beta b] [--lambda l] [--limit n] [--samples m] [--seed s]
+*/
+pub fn levy_test(args: &[String]) {
+    let betas: Vec<Option<f32>> = match get_flag(args, "--beta") {
+        Some(beta) => vec![Some(beta.parse().expect("Invalid beta"))],
+        None => vec![None, Some(0.5), Some(1.0), Some(1.5), Some(2.0)]
+    };
+    let lambda: f32 = get_flag(args, "--lambda").map_or(Parameters::standard().lambda, |l| l.parse().expect("Invalid lambda"));
+    let limit: usize = get_flag(args, "--limit").map_or(50, |n| n.parse().expect("Invalid limit"));
+    let samples: usize = get_flag(args, "--samples").map_or(100000, |m| m.parse().expect("Invalid number of samples"));
+    let seed: u64 = get_flag(args, "--seed").map_or(0, |s| s.parse().expect("Invalid seed"));
+    // Bonferroni correction over the two tests for every beta
+    let alpha = 0.01 / (2 * betas.len()) as f64;
+
+    println!("Lévy flight steps with lambda = {}, limit = {}, {} samples per test", lambda, limit, samples);
+    let mut table = table!(["Beta", "Sigma", "P(0)", "Mean", "Fit chi2 (df)", "Fit p", "Direct chi2 (df)", "Direct p", "Consistent"]);
+    let mut consistent = true;
+    for beta in betas {
+        let StepCheck { probabilities, fit, direct } = check_steps(lambda, beta, limit, samples, seed);
+        let ok = fit.2 >= alpha && direct.2 >= alpha;
+        consistent &= ok;
+        let mean: f64 = probabilities.iter().enumerate().map(|(i, p)| i as f64 * p).sum();
+        let (name, sigma) = match beta {
+            Some(beta) if beta < 2.0 => (beta.to_string(), format!("{:.4}", mantegna_sigma(beta as f64))),
+            Some(beta) => (beta.to_string(), String::from("1.0000")),
+            None => (String::from("c/N^2"), String::from("-"))
+        };
+        table.add_row(row![name, sigma, format!("{:.4}", probabilities[0]), format!("{:.3}", mean),
+            format!("{:.2} ({})", fit.0, fit.1), format!("{:.4}", fit.2),
+            format!("{:.2} ({})", direct.0, direct.1), format!("{:.4}", direct.2), ok]);
+    }
+    table.printstd();
+    if consistent {
+        println!("All samples are consistent with the distribution (alpha = {:.4}).", alpha);
+    } else {
+        println!("Some samples are NOT consistent with the distribution (alpha = {:.4}).", alpha);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Family-wise significance level 0.01 over the two tests of the four samplers
+    const ALPHA: f64 = 0.01 / 8.0;
+
+    fn assert_consistent(beta: Option<f32>, seed: u64) {
+        for (lambda, limit) in [(Parameters::standard().lambda, 50), (0.5, 125)] {
+            let check = check_steps(lambda, beta, limit, 20000, seed);
+            assert!((check.probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            assert!(check.fit.2 >= ALPHA, "beta {:?}, lambda {}: fit {:?}", beta, lambda, check.fit);
+            assert!(check.direct.2 >= ALPHA, "beta {:?}, lambda {}: direct {:?}", beta, lambda, check.direct);
+        }
+    }
+
+    #[test]
+    fn default_steps() {
+        assert_consistent(None, 1);
+    }
+
+    #[test]
+    fn mantegna_steps() {
+        for (beta, seed) in [(0.5, 2), (1.5, 3), (2.0, 4)] {
+            assert_consistent(Some(beta), seed);
+        }
+    }
+
+    #[test]
+    fn cdf_is_monotone() {
+        for beta in [None, Some(0.5), Some(1.5), Some(2.0)] {
+            let table = levy_table(beta);
+            let steps: Vec<f64> = (0..200).map(|i| 10f64.powf(-6.0 + i as f64 * 0.08)).collect();
+            assert!(steps.windows(2).all(|pair| table.cdf(pair[0]) <= table.cdf(pair[1])));
+            for p in [0.1, 0.5, 0.9] {
+                assert!((table.cdf(table.quantile(p)) - p).abs() < 1e-6);
+            }
+        }
+    }
+}
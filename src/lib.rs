@@ -0,0 +1,599 @@
+#![warn(non_snake_case)]
+#![allow(dead_code)]
+#![allow(unused_variables)]
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod structs;
+pub mod greedy;
+pub mod pollinators;
+pub mod registry;
+pub mod bench;
+pub mod generators;
+pub mod info;
+pub mod islands;
+pub mod diversity;
+pub mod statistics;
+pub mod compare;
+pub mod tuning;
+pub mod checkpoint;
+pub mod observers;
+pub mod levy;
+pub mod crossover;
+pub mod hea;
+pub mod annealing;
+pub mod ils;
+pub mod partial;
+pub mod control;
+pub mod restart;
+pub mod archive;
+pub mod pareto;
+pub mod warm;
+pub mod dynamic;
+
+use std::time::Instant;
+
+use rand::{Rng, thread_rng};
+use rand::seq::{index};
+use rand_distr::{StandardNormal, Uniform};
+use rayon::prelude::*;
+use itertools::Itertools;
+
+use self::structs::*;
+use self::greedy::*;
+use self::registry::*;
+use self::bench::*;
+use self::generators::*;
+use self::info::*;
+use self::islands::*;
+use self::diversity::*;
+use self::compare::*;
+use self::tuning::*;
+use self::checkpoint::*;
+use self::observers::*;
+use self::levy::*;
+use self::hea::*;
+use self::annealing::*;
+use self::ils::*;
+use self::partial::*;
+use self::control::*;
+use self::restart::*;
+use self::archive::*;
+use self::pareto::*;
+use self::warm::*;
+use self::dynamic::*;
+
+pub const MAX_GEN: usize = 50000;
+pub const POP_SIZE: usize = 20;
+
+pub fn discrete_fpa(g: &Graph, pollinator: &Pollinator, k: usize, options: Option<Parameters>, stop: Option<usize>, budget: Budget, seed: u64) -> RunResult {
+    let options = options.unwrap_or_else(Parameters::standard);
+    continue_fpa(g, pollinator, &mut SolverState::new(options.pop_size, k, seed), options, stop, budget, &mut ())
+}
+
+/*
+*   Run DFPA from a given state until the budget is used up or k reaches stop. The observer is called after
+*   every generation and can stop the run early, which can then be continued from the state.
+*/
+pub fn continue_fpa(g: &Graph, pollinator: &Pollinator, state: &mut SolverState, options: Parameters, stop: Option<usize>, budget: Budget, observer: &mut dyn Observer) -> RunResult {
+    let mut dfpa = Dfpa::new(pollinator, state.pop_size, options);
+    descend(g, &mut dfpa, state, options, stop, budget, observer)
+}
+
+/*
+*   A population based solver for a fixed k, driven by descend
+*/
+pub trait Evolution {
+    /*
+    *   A new population for k, random colorings by default
+    */
+    fn populate(&mut self, g: &Graph, n: usize, k: usize, seed: u64, generation: usize) -> Vec<Coloring> {
+        g.populate(&mut rng_stream(seed, generation, POPULATE_STREAM), n, k)
+    }
+
+    /*
+    *   Evolve the population by one generation, random numbers are drawn from the streams of generation
+    *   result.generations. Adds the evaluations and restarts of the generation to result.
+    */
+    fn generation(&mut self, g: &Graph, pop: &mut [Coloring], best: &Coloring, k: usize, seed: u64, result: &mut RunResult);
+
+    /*
+    *   Called when the population contains a legal coloring for k, return false to keep evolving
+    *   it for k before continuing with k - 1
+    */
+    fn accept(&mut self, pop: &[Coloring], k: usize) -> bool {
+        true
+    }
+
+    /*
+    *   The parameter values of the latest generation, for solvers that adapt them during the run
+    */
+    fn control(&self) -> Option<ControlPoint> {
+        None
+    }
+
+    /*
+    *   Write what the solver adapted during the run to the state, so checkpoints keep it
+    */
+    fn save(&self, state: &mut SolverState) {}
+
+    /*
+    *   Continue with what the solver adapted before the state was saved
+    */
+    fn restore(&mut self, state: &SolverState) {}
+}
+
+/*
+*   The k-descent shared by the solvers: evolve a population for k until it contains a legal coloring,
+*   then continue with k - 1, until the budget is used up or k reaches stop. Distinct legal colorings of every k
+*   are kept in the archive of the state, with reinject those of k + 1 replace the flowers of a population restart.
+*/
+pub fn descend(g: &Graph, evolution: &mut dyn Evolution, state: &mut SolverState, options: Parameters, stop: Option<usize>, budget: Budget, observer: &mut dyn Observer) -> RunResult {
+    // Time is counted from the start of this call on top of the time already spent on the state,
+    // which may come from a checkpoint written before a reboot
+    let start = Instant::now();
+    let offset = state.elapsed;
+    let elapsed = || offset + start.elapsed().as_secs_f64();
+    let seed = state.seed;
+    let n = state.pop_size;
+    state.archive.capacity = options.archive_size;
+    evolution.restore(state);
+    loop {
+        //println!("Evaluating k = {}.", k);
+        if state.k < stop.unwrap_or(1) {
+            break;
+        }
+        if state.pop.is_empty() {
+            state.pop = evolution.populate(g, n, state.k, seed, state.result.generations);
+            state.iteration = 0;
+        }
+        if state.iteration >= budget.max_gen || budget.is_exceeded(elapsed()) {
+            break;
+        }
+        let k = state.k;
+        let pop = &mut state.pop;
+        let best = pop.par_iter().min_by_key(|x| x.tot_conflicts).unwrap().clone();
+        state.archive.offer_all(k, pop);
+
+        if options.track_diversity {
+            println!("Generation {}: k = {}, best conflicts = {}, mean distance = {:.2}, distinct flowers = {}",
+                state.result.generations, k, best.tot_conflicts, mean_pairwise_distance(pop), distinct_flowers(pop));
+        }
+        
+        if best.tot_conflicts == 0 && evolution.accept(pop, k) {
+            //println!("\tFound solution at iteration {}.", it);
+            state.result.k = k;
+            state.result.time_to_best = elapsed();
+            observer.improvement(state, &best, state.result.time_to_best);
+            state.best = Some(best);
+            state.k -= 1;
+            state.pop.clear();
+            continue;
+        }
+
+        let population_restarts = state.result.population_restarts;
+        evolution.generation(g, pop, &best, k, seed, &mut state.result);
+        if options.reinject && state.result.population_restarts > population_restarts {
+            state.archive.reinject(g, k, pop, best.index);
+        }
+        state.result.generations += 1;
+        state.iteration += 1;
+
+        state.elapsed = elapsed();
+        evolution.save(state);
+        let mut stats = GenerationStats::of(state, state.elapsed);
+        stats.control = evolution.control();
+        if observer.generation(state, &stats) {
+            break;
+        }
+    }
+    state.elapsed = elapsed();
+    state.result.total_time = state.elapsed;
+    state.result.clone()
+}
+
+/*
+*   DFPA: every generation pollinates all flowers in parallel. switch_p and lambda follow the control
+*   strategy of the parameters.
+*/
+pub struct Dfpa {
+    contexts: Vec<FlowerContext>,
+    levy: LevyFlight,
+    parent_count: usize,
+    options: Parameters,
+    controller: Controller,
+    stagnation: Stagnation
+}
+
+impl Dfpa {
+    pub fn new(pollinator: &Pollinator, n: usize, options: Parameters) -> Dfpa {
+        let contexts = pollinator.contexts(n);
+        let parent_count = contexts.iter().map(|context| context.global.parents()).max().unwrap_or(1).max(options.restart.parents());
+        Dfpa {contexts, levy: LevyFlight::of(&options), parent_count, options, controller: Controller::new(options), stagnation: Stagnation::default()}
+    }
+}
+
+impl Evolution for Dfpa {
+    fn generation(&mut self, g: &Graph, pop: &mut [Coloring], best: &Coloring, k: usize, seed: u64, result: &mut RunResult) {
+        let Dfpa { contexts, levy, parent_count, options, controller, stagnation } = self;
+        controller.adjust(options, levy, result.generations, k);
+        let parents = if *parent_count > 1 { best_flowers(pop, *parent_count) } else { vec![best.clone()] };
+        let generation = result.generations;
+
+        if stagnation.k != k || best.tot_conflicts < stagnation.best_conflicts {
+            *stagnation = Stagnation { k, best_conflicts: best.tot_conflicts, generations: 0 };
+        } else {
+            stagnation.generations += 1;
+        }
+        if options.stagnation_limit > 0 && stagnation.generations >= options.stagnation_limit {
+            // Restart every flower but the best instead of pollinating
+            let restarts = pop.par_iter_mut().filter(|x| x.index != best.index).map(|x| {
+                let mut rng = rng_stream(seed, generation, x.index);
+                RestartCounts::of(Some(options.restart.apply(&mut rng, g, x, &parents, k, levy)))
+            }).reduce(RestartCounts::default, |a, b| a + b);
+            result.count_restarts(restarts);
+            stagnation.generations = 0;
+            result.evaluations += pop.len() - 1;
+            result.population_restarts += 1;
+            return;
+        }
+
+        // The replacement rule compares offspring with the population of the previous generation
+        let prev = if options.min_distance > 0 { pop.to_vec() } else { Vec::new() };
+
+        // Iterate through all solutions (in parallel)
+        let (restarts, successes) = pop.par_iter_mut().zip(contexts.par_iter_mut()).map(|(x, context)| {
+            let mut rng = rng_stream(seed, generation, x.index);
+            let before = x.tot_conflicts;
+            let restart = pollinate(context, &mut rng, g, x, &parents, &prev, k, *options, levy);
+            (RestartCounts::of(restart), (restart.is_none() && x.tot_conflicts < before) as usize)
+        }).reduce(|| (RestartCounts::default(), 0), |a, b| (a.0 + b.0, a.1 + b.1));
+        controller.record(successes, pop.len());
+        result.evaluations += pop.len();
+        result.restarts += restarts.total();
+        result.count_restarts(restarts);
+    }
+
+    fn control(&self) -> Option<ControlPoint> {
+        // Mean selection probabilities of the adaptive local operators of the flowers
+        let mut operators: Vec<f64> = Vec::new();
+        for context in &self.contexts {
+            let selection = context.local.selection();
+            operators.resize(selection.len(), 0.0);
+            for (total, probability) in operators.iter_mut().zip(selection) {
+                *total += probability / self.contexts.len() as f64;
+            }
+        }
+        if self.options.control.is_fixed() && operators.is_empty() {
+            return None;
+        }
+        Some(self.controller.point(&self.options, operators))
+    }
+
+    fn save(&self, state: &mut SolverState) {
+        state.stagnation = self.stagnation;
+        let qualities: Vec<Vec<f64>> = self.contexts.iter().map(|context| context.local.qualities()).collect();
+        if self.options.control.is_fixed() && qualities.iter().all(|quality| quality.is_empty()) {
+            return;
+        }
+        state.adaptive = Some(AdaptiveState {
+            controller: self.controller.clone(),
+            switch_p: self.options.switch_p,
+            lambda: self.options.lambda,
+            qualities
+        });
+    }
+
+    fn restore(&mut self, state: &SolverState) {
+        self.stagnation = state.stagnation;
+        let Some(adaptive) = &state.adaptive else {
+            return;
+        };
+        self.controller.restore(&mut self.options, &mut self.levy, adaptive);
+        for (context, qualities) in self.contexts.iter_mut().zip(&adaptive.qualities) {
+            context.local.restore_qualities(qualities);
+        }
+    }
+}
+
+/*
+*   Pollinate a single flower with the operators of its context, the flower is replaced by its offspring
+*   if that is at least as good. Parents are the best flowers of the population, best first.
+*   With a min_distance, offspring closer than that to any flower of pop (other than x) are rejected.
+*   Returns the strategy the flower was restarted with if it reached its lifetime limit.
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn pollinate(context: &mut FlowerContext, rng: &mut Rand, g: &Graph, x: &mut Coloring, parents: &[Coloring], pop: &[Coloring], k: usize, options: Parameters, levy: &LevyFlight) -> Option<Restart> {
+    let Parameters { switch_p, lifetime_limit, min_distance, .. } = options;
+    let best = &parents[0];
+    let p = rng.gen_bool(switch_p);
+
+    if x.lifetime >= lifetime_limit && p && *x != *best {
+        return Some(options.restart.apply(rng, g, x, parents, k, levy));
+    }
+    
+    let x_new = &mut context.offspring;
+    x_new.copy_from(x);
+    if p && *x != *best {
+        // Biotic pollination
+        let count = context.global.parents().min(parents.len());
+        context.global.apply(rng, g, &parents[..count], x, x_new, k, levy);
+    } else {
+        // Abiotic pollination
+        context.local.apply(rng, g, x, x_new, k, levy);
+    }
+    
+    if x_new.tot_conflicts <= x.tot_conflicts && (min_distance == 0 || is_diverse(x_new, pop, x, min_distance)) {
+        if x_new.tot_conflicts == x.tot_conflicts {
+            x.lifetime += 1;
+        } else {
+            x.lifetime = 0;
+        }
+        // The previous flower becomes the buffer for the next offspring
+        std::mem::swap(x, x_new);
+    } else {
+        x.lifetime += 1;
+    }
+    None
+}
+
+/*
+*   Copies of the count flowers with the fewest conflicts, best first
+*/
+pub fn best_flowers(pop: &[Coloring], count: usize) -> Vec<Coloring> {
+    pop.iter().sorted_by_key(|x| x.tot_conflicts).take(count).cloned().collect()
+}
+
+/*
+*   Get the value following a flag, e.g. "--seed 42"
+*/
+pub fn get_flag<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1)).map(|s| s.as_str())
+}
+
+/*
+*   Flags without a value, every other flag is followed by its value
+*/
+pub const SWITCHES: [&str; 4] = ["--json", "--diversity", "--progress", "--reinject"];
+
+/*
+*   Get the arguments that are neither flags nor flag values
+*/
+pub fn positional_args(args: &[String]) -> Vec<&str> {
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg.starts_with("--") {
+            if !SWITCHES.contains(&arg.as_str()) {
+                iter.next();
+            }
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+    positional
+}
+
+/*
+*   Run the command line interface, args[0] is the program name
+*/
+pub fn run(args: &[String]) {
+    if args[1] == "bench" {
+        return bench(&args[2..], "suites/final.json");
+    } else if args[1] == "bench_param" {
+        return bench(&args[2..], "suites/parameters.json");
+    } else if args[1] == "bench_pol" {
+        return bench(&args[2..], "suites/pollinators.json");
+    } else if args[1] == "generate" {
+        return generate(&args[2..]);
+    } else if args[1] == "info" {
+        return info(&args[2..]);
+    } else if args[1] == "island" {
+        return island(&args[2..]);
+    } else if args[1] == "compare" {
+        return compare(&args[2..]);
+    } else if args[1] == "tune" {
+        return tune(&args[2..]);
+    } else if args[1] == "hea" {
+        return hea_cli(&args[2..]);
+    } else if args[1] == "sa" {
+        return annealing_cli(&args[2..]);
+    } else if args[1] == "ils" {
+        return ils_cli(&args[2..]);
+    } else if args[1] == "partial" {
+        return partial_cli(&args[2..]);
+    } else if args[1] == "pareto" {
+        return pareto_cli(&args[2..]);
+    } else if args[1] == "dynamic" {
+        return dynamic_cli(&args[2..]);
+    } else if args[1] == "levy" {
+        return levy_test(&args[2..]);
+    } else if args[1] == "solve" {
+        return solve(&args[2..]);
+    }
+    solve(&args[1..]);
+}
+
+/*
+*   The pollinator chosen on the command line, either by name (default CA) or as a global and
+*   a local operator, e.g. "--global levy_pop1 --local tabucol"
+*/
+pub fn pollinator_name(args: &[String]) -> String {
+    match (get_flag(args, "--global"), get_flag(args, "--local")) {
+        (None, None) => get_flag(args, "--pollinator").unwrap_or("CA").to_string(),
+        (global, local) => format!("{}+{}", global.unwrap_or("levy_pop1"), local.unwrap_or("change_all_critical_opt"))
+    }
+}
+
+/*
+*   Solve a single graph with DFPA, "solve" can be left out.
+*   Usage: solve <graph> [--pollinator name | --global name --local name] [--seed s] [--beta b] [--diversity] [--min-distance d] [--checkpoint file [--checkpoint-every n]]
+*                [--progress] [--trace file.csv] [--restart random|perturb|dsatur|greedy|crossover] [--stagnation n] [--control fixed|linear:<switch_p>:<lambda>:<generations>|cosine:...|success:<factor>:<window>]
+*                [--archive-size n] [--reinject] [--archive file.json] [--init solution [--init-flowers n]]
+*          solve --resume <file> [--checkpoint-every n] [--progress] [--trace file.csv] [--archive file.json]
+*/
+fn solve(args: &[String]) {
+    let interval: usize = get_flag(args, "--checkpoint-every").map_or(1000, |value| value.parse().expect("Invalid checkpoint interval"));
+    let checkpoint = match get_flag(args, "--resume") {
+        Some(file_name) => {
+            let checkpoint = Checkpoint::read(file_name);
+            println!("Resuming {} at k = {}, generation {}", checkpoint.graph, checkpoint.state.k, checkpoint.state.result.generations);
+            (file_name.to_string(), checkpoint)
+        }
+        None => {
+            let positional = positional_args(args);
+            if positional.len() != 1 {
+                panic!("Unsupported argument!");
+            }
+            let mut options = Parameters::standard();
+            options.track_diversity = args.iter().any(|arg| arg == "--diversity");
+            if let Some(value) = get_flag(args, "--beta") {
+                options.beta = Some(value.parse().expect("Invalid beta"));
+            }
+            if let Some(value) = get_flag(args, "--min-distance") {
+                options.min_distance = value.parse().expect("Invalid minimum distance");
+            }
+            if let Some(value) = get_flag(args, "--restart") {
+                options.restart = value.parse().unwrap_or_else(|err| panic!("{}", err));
+            }
+            if let Some(value) = get_flag(args, "--stagnation") {
+                options.stagnation_limit = value.parse().expect("Invalid stagnation limit");
+            }
+            if let Some(value) = get_flag(args, "--archive-size") {
+                options.archive_size = value.parse().expect("Invalid archive size");
+            }
+            options.reinject = args.iter().any(|arg| arg == "--reinject");
+            if let Some(value) = get_flag(args, "--control") {
+                options.control = value.parse().unwrap_or_else(|err| panic!("{}", err));
+            }
+            let seed = seed_flag(args);
+            let checkpoint = Checkpoint {
+                graph: positional[0].to_string(),
+                pollinator: pollinator_name(args),
+                parameters: options,
+                budget: Budget::standard(),
+                stop: None,
+                // k is set once the greedy solution is known
+                state: SolverState::new(options.pop_size, 0, seed)
+            };
+            (get_flag(args, "--checkpoint").unwrap_or_default().to_string(), checkpoint)
+        }
+    };
+    let (file_name, mut checkpoint) = checkpoint;
+
+    let path = format!("graphs/{}.col", checkpoint.graph);
+    let graph = Graph::read(&path);
+    let max_d = graph.max_degree();
+    //println!("Graph: {:?}", graph);
+    println!("Num nodes: {}", graph.len());
+    println!("Max degree: {}", max_d);
+    println!("Seed: {}", checkpoint.state.seed);
+
+    let now = Instant::now();
+    if get_flag(args, "--resume").is_none() {
+        if let Some(init) = get_flag(args, "--init") {
+            // Warm start from a coloring of a similar graph, completed greedily for vertices it does not color
+            let solution = read_solution(init, graph.len());
+            if graph.len() > 0 && solution.iter().all(|&color| color == 0) {
+                panic!("The initial coloring {} does not color any vertex of {}", init, checkpoint.graph);
+            }
+            let (initial, completed) = complete(&graph, &solution);
+            let k = initial.get_k();
+            assert!(initial.tot_conflicts == 0 && initial.solution.iter().all(|&color| (1..=k).contains(&color)),
+                "Completing {} did not give a legal coloring with colors 1..={}", init, k);
+            println!("Initial coloring: {} colors, {} vertices completed", k, completed);
+            if k < 2 {
+                return print_trivial(k);
+            }
+            let pop_size = checkpoint.state.pop_size;
+            let count = get_flag(args, "--init-flowers").map_or(pop_size, |value| value.parse().expect("Invalid number of initial flowers"));
+            checkpoint.state = SolverState::new(pop_size, k - 1, checkpoint.state.seed);
+            checkpoint.state.pop = warm_population(&graph, &initial, count, pop_size, k - 1, checkpoint.state.seed);
+            checkpoint.state.best = Some(initial);
+        } else {
+            let (greedy, _greedy_sol) = dsatur2(&graph);
+            if greedy < 2 {
+                return print_trivial(greedy);
+            }
+            checkpoint.state = SolverState::new(checkpoint.state.pop_size, greedy-1, checkpoint.state.seed);
+        }
+    }
+    let mut observers: Vec<Box<dyn Observer>> = Vec::new();
+    if !file_name.is_empty() {
+        Checkpointer::catch_interrupt();
+        observers.push(Box::new(Checkpointer { file_name: file_name.clone(), interval, template: checkpoint.clone() }));
+    }
+    if args.iter().any(|arg| arg == "--progress") {
+        observers.push(Box::new(ProgressPrinter::new(1.0)));
+    }
+    if let Some(trace) = get_flag(args, "--trace") {
+        observers.push(Box::new(TraceWriter::open(trace, checkpoint.state.result.generations)));
+    }
+    let pollinator = Pollinator::by_name(&checkpoint.pollinator).unwrap_or_else(|err| panic!("{}", err));
+    let mut state = checkpoint.state.clone();
+    let result = continue_fpa(&graph, &pollinator, &mut state, checkpoint.parameters, checkpoint.stop, checkpoint.budget, &mut observers);
+    let elapsed_time = now.elapsed();
+
+    if Checkpointer::interrupted() {
+        println!("Interrupted, continue with: solve --resume {}", file_name);
+    }
+    print_result(&result);
+    println!("Restarts: {} ({}), population restarts: {}", result.restarts, checkpoint.parameters.restart, result.population_restarts);
+    print_restarts(&result);
+    if let Some(archive) = get_flag(args, "--archive") {
+        state.archive.write(archive);
+        println!("Archived {} colorings for {} values of k in {}", state.archive.len(), state.archive.levels.len(), archive);
+    }
+    println!("Running DFPA took {} seconds.", elapsed_time.as_secs());
+}
+
+/*
+*   The seed given with --seed, or a random one
+*/
+pub fn seed_flag(args: &[String]) -> u64 {
+    get_flag(args, "--seed").map_or_else(|| thread_rng().gen(), |s| s.parse().expect("Invalid seed"))
+}
+
+/*
+*   A legal coloring with fewer than 2 colors means the graph has no edges, there is nothing to descend from
+*/
+fn print_trivial(k: usize) {
+    println!("The graph has no edges, {} colors is optimal", k);
+    println!("Num colors: {}", k);
+}
+
+fn print_result(result: &RunResult) {
+    println!("Num colors: {}", result.k);
+    println!("Generations: {}, found after {:.2} seconds.", result.generations, result.time_to_best);
+}
+
+fn print_restarts(result: &RunResult) {
+    if !result.restarts_by_strategy.is_empty() {
+        println!("Restarted flowers: {}", result.restarts_by_strategy.iter().map(|(strategy, count)| format!("{} {}", strategy, count)).join(", "));
+    }
+}
+
+/*
+*   Command line shared by the single solvers (hea, sa, ils, partial and pareto). Reads the graph, prints
+*   the solver settings with describe and runs search from k = --k, by default below colors fewer than DSATUR.
+*   Usage: <solver> <graph> [--k n] [--seed s] [solver flags]
+*/
+pub fn solver_cli(args: &[String], solver: &str, below: usize, describe: impl FnOnce(), search: impl FnOnce(&Graph, usize, u64) -> RunResult) {
+    let positional = positional_args(args);
+    let name = positional.first().expect("Missing graph name");
+    let graph = Graph::read(&format!("graphs/{}.col", name));
+    println!("Num nodes: {}", graph.len());
+    describe();
+
+    let seed = seed_flag(args);
+    println!("Seed: {}", seed);
+
+    let now = Instant::now();
+    let k = get_flag(args, "--k").map_or_else(|| dsatur2(&graph).0 - below, |value| value.parse().expect("Invalid k"));
+    let result = search(&graph, k, seed);
+    let elapsed_time = now.elapsed();
+
+    print_result(&result);
+    println!("Restarts: {}", result.restarts);
+    print_restarts(&result);
+    println!("Running {} took {} seconds.", solver, elapsed_time.as_secs());
+}
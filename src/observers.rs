@@ -0,0 +1,160 @@
+use super::*;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/*
+*   Statistics of the population after a generation, times are in seconds
+*/
+pub struct GenerationStats {
+    pub generation: usize,
+    pub k: usize,
+    pub best_conflicts: usize,
+    pub mean_conflicts: f64,
+    pub restarts: usize,
+    pub elapsed: f64,
+    // The parameter values of solvers with adaptive control
+    pub control: Option<ControlPoint>
+}
+
+impl GenerationStats {
+    pub fn of(state: &SolverState, elapsed: f64) -> GenerationStats {
+        let pop = &state.pop;
+        GenerationStats {
+            generation: state.result.generations,
+            k: state.k,
+            best_conflicts: pop.iter().map(|x| x.tot_conflicts).min().unwrap_or(0),
+            mean_conflicts: pop.iter().map(|x| x.tot_conflicts).sum::<usize>() as f64 / pop.len().max(1) as f64,
+            restarts: state.result.restarts,
+            elapsed,
+            control: None
+        }
+    }
+}
+
+/*
+*   Hook into a running solver. Called after every generation and whenever a legal coloring
+*   with fewer colors is found.
+*/
+pub trait Observer {
+    /*
+    *   Return true to stop the run, it can be continued from the state later
+    */
+    fn generation(&mut self, state: &SolverState, stats: &GenerationStats) -> bool {
+        false
+    }
+
+    fn improvement(&mut self, state: &SolverState, coloring: &Coloring, elapsed: f64) {}
+}
+
+impl Observer for () {}
+
+impl Observer for Vec<Box<dyn Observer>> {
+    fn generation(&mut self, state: &SolverState, stats: &GenerationStats) -> bool {
+        // Every observer sees every generation, even when an earlier one asks to stop
+        let mut stop = false;
+        for observer in self.iter_mut() {
+            stop |= observer.generation(state, stats);
+        }
+        stop
+    }
+
+    fn improvement(&mut self, state: &SolverState, coloring: &Coloring, elapsed: f64) {
+        for observer in self.iter_mut() {
+            observer.improvement(state, coloring, elapsed);
+        }
+    }
+}
+
+/*
+*   Prints the progress of a run at most every interval seconds and on every improvement
+*/
+pub struct ProgressPrinter {
+    pub interval: f64,
+    last: f64
+}
+
+impl ProgressPrinter {
+    pub fn new(interval: f64) -> ProgressPrinter {
+        ProgressPrinter {interval, last: f64::NEG_INFINITY}
+    }
+}
+
+impl Observer for ProgressPrinter {
+    fn generation(&mut self, state: &SolverState, stats: &GenerationStats) -> bool {
+        if stats.elapsed - self.last >= self.interval {
+            self.last = stats.elapsed;
+            println!("[{:>8.1}s] generation {:>8}  k = {:<4} best conflicts = {:<5} mean conflicts = {:<8.2} restarts = {}",
+                stats.elapsed, stats.generation, stats.k, stats.best_conflicts, stats.mean_conflicts, stats.restarts);
+        }
+        false
+    }
+
+    fn improvement(&mut self, state: &SolverState, coloring: &Coloring, elapsed: f64) {
+        println!("[{:>8.1}s] generation {:>8}  found a legal coloring with k = {}", elapsed, state.result.generations, state.result.k);
+    }
+}
+
+/*
+*   Writes the statistics of every generation to a CSV file for convergence plots
+*/
+pub struct TraceWriter {
+    file: BufWriter<File>
+}
+
+impl TraceWriter {
+    /*
+    *   Continue the trace file of a run at the given generation. Rows of later generations, written
+    *   by an interrupted run after its last checkpoint, are dropped, so a new run starts a new trace.
+    */
+    pub fn open(file_name: &str, generations: usize) -> TraceWriter {
+        let content = std::fs::read_to_string(file_name).unwrap_or_default();
+        let rows = content.lines().skip(1).filter(|row| {
+            row.split(',').next().and_then(|generation| generation.parse::<usize>().ok()).is_some_and(|generation| generation <= generations)
+        });
+        let mut file = BufWriter::new(File::create(file_name).unwrap_or_else(|err| panic!("Could not open {}: {}", file_name, err)));
+        writeln!(file, "generation,k,best_conflicts,mean_conflicts,restarts,elapsed,switch_p,lambda,success_rate,operators").expect("Could not write trace");
+        for row in rows {
+            writeln!(file, "{}", row).expect("Could not write trace");
+        }
+        TraceWriter {file}
+    }
+}
+
+impl Observer for TraceWriter {
+    fn generation(&mut self, state: &SolverState, stats: &GenerationStats) -> bool {
+        // The control columns are empty for fixed parameters, operators are separated by semicolons
+        let control = stats.control.as_ref().map_or_else(|| String::from(",,,"), |point| format!("{:.6},{:.4},{:.4},{}",
+            point.switch_p, point.lambda, point.success_rate, point.operators.iter().map(|p| format!("{:.4}", p)).join(";")));
+        writeln!(self.file, "{},{},{},{:.4},{},{:.6},{}", stats.generation, stats.k, stats.best_conflicts, stats.mean_conflicts, stats.restarts, stats.elapsed, control)
+            .expect("Could not write trace");
+        false
+    }
+}
+
+/*
+*   Collects the parameter values of a run with adaptive control, every interval generations
+*   and whenever k changes
+*/
+pub struct TrajectoryLog {
+    pub interval: usize,
+    pub points: Vec<ControlPoint>
+}
+
+impl TrajectoryLog {
+    pub fn new(interval: usize) -> TrajectoryLog {
+        TrajectoryLog {interval, points: Vec::new()}
+    }
+}
+
+impl Observer for TrajectoryLog {
+    fn generation(&mut self, state: &SolverState, stats: &GenerationStats) -> bool {
+        if let Some(point) = &stats.control {
+            let new_k = self.points.last().is_none_or(|last| last.k != point.k);
+            if new_k || point.generation.is_multiple_of(self.interval.max(1)) {
+                self.points.push(point.clone());
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,324 @@
+use super::structs::*;
+use super::registry::*;
+use super::levy::*;
+use super::*;
+
+use std::fs;
+use std::str::FromStr;
+use prettytable::*;
+use serde::{Serialize, Deserialize};
+
+/*
+*   The objective traded off against the number of colors
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Objective {
+    // Size of the largest color class
+    Balance,
+    // Sum over the color classes of the largest vertex weight in the class, like batches that take
+    // as long as their longest job
+    Weighted
+}
+
+impl FromStr for Objective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Objective, String> {
+        match s {
+            "balance" => Ok(Objective::Balance),
+            "weighted" => Ok(Objective::Weighted),
+            _ => Err(format!("Unknown objective {}, use balance or weighted", s))
+        }
+    }
+}
+
+/*
+*   The secondary objective of a coloring, which is minimized
+*/
+#[derive(Clone)]
+pub struct Secondary {
+    pub objective: Objective,
+    // Weight of every vertex, only used by the weighted objective
+    pub weights: Vec<f64>
+}
+
+impl Secondary {
+    pub fn balance() -> Secondary {
+        Secondary {objective: Objective::Balance, weights: Vec::new()}
+    }
+
+    pub fn weighted(weights: Vec<f64>) -> Secondary {
+        Secondary {objective: Objective::Weighted, weights}
+    }
+
+    pub fn evaluate(&self, x: &Coloring) -> f64 {
+        let colors = x.solution.iter().copied().max().unwrap_or(0) + 1;
+        match self.objective {
+            Objective::Balance => {
+                let mut sizes = vec![0; colors];
+                for &color in &x.solution {
+                    sizes[color] += 1;
+                }
+                sizes.into_iter().max().unwrap_or(0) as f64
+            }
+            Objective::Weighted => {
+                let mut heaviest = vec![0.0; colors];
+                for (&color, &weight) in x.solution.iter().zip(&self.weights) {
+                    heaviest[color] = f64::max(heaviest[color], weight);
+                }
+                heaviest.iter().sum()
+            }
+        }
+    }
+}
+
+/*
+*   Read vertex weights, whitespace separated numbers in vertex order
+*/
+pub fn read_weights(file_name: &str, n: usize) -> Vec<f64> {
+    let content = fs::read_to_string(file_name).unwrap_or_else(|err| panic!("Could not read weights {}: {}", file_name, err));
+    let weights: Vec<f64> = content.split_whitespace().map(|weight| weight.parse().expect("Invalid weight")).collect();
+    assert!(weights.len() == n, "Expected {} weights in {}, found {}", n, file_name, weights.len());
+    weights
+}
+
+//---------------------------------------------------------------------------------------//
+
+/*
+*   True if a is at least as good as b in both objectives and better in one, both are minimized
+*/
+pub fn dominates(a: (f64, f64), b: (f64, f64)) -> bool {
+    a.0 <= b.0 && a.1 <= b.1 && (a.0 < b.0 || a.1 < b.1)
+}
+
+/*
+*   Fast non-dominated sorting (Deb et al. 2002): the indices of the points by front, the first front
+*   is not dominated by any point, the second only by points of the first and so on
+*/
+pub fn non_dominated_sort(points: &[(f64, f64)]) -> Vec<Vec<usize>> {
+    let n = points.len();
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut dominators = vec![0; n];
+    for i in 0..n {
+        for j in 0..n {
+            if dominates(points[i], points[j]) {
+                dominated[i].push(j);
+            } else if dominates(points[j], points[i]) {
+                dominators[i] += 1;
+            }
+        }
+    }
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..n).filter(|&i| dominators[i] == 0).collect();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &i in &front {
+            for &j in &dominated[i] {
+                dominators[j] -= 1;
+                if dominators[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        next.sort_unstable();
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/*
+*   Crowding distance of every point of a front: the normalized side lengths of the box formed by its
+*   neighbors in the front, infinite for the extremes
+*/
+pub fn crowding_distance(front: &[usize], points: &[(f64, f64)]) -> Vec<f64> {
+    let mut distance = vec![0.0; front.len()];
+    for objective in 0..2 {
+        let value = |i: usize| if objective == 0 { points[front[i]].0 } else { points[front[i]].1 };
+        let order: Vec<usize> = (0..front.len()).sorted_by(|&a, &b| value(a).total_cmp(&value(b))).collect();
+        let (first, last) = (order[0], *order.last().unwrap());
+        distance[first] = f64::INFINITY;
+        distance[last] = f64::INFINITY;
+        if value(last) > value(first) {
+            for w in order.windows(3) {
+                distance[w[1]] += (value(w[2]) - value(w[0])) / (value(last) - value(first));
+            }
+        }
+    }
+    distance
+}
+
+/*
+*   Choose count of the points, whole fronts first and the least crowded points of the front that does not fit
+*/
+pub fn select(points: &[(f64, f64)], count: usize) -> Vec<usize> {
+    let mut chosen = Vec::with_capacity(count);
+    for front in non_dominated_sort(points) {
+        if chosen.len() + front.len() <= count {
+            chosen.extend(front);
+            continue;
+        }
+        let distance = crowding_distance(&front, points);
+        let order = (0..front.len()).sorted_by(|&a, &b| distance[b].total_cmp(&distance[a]));
+        chosen.extend(order.take(count - chosen.len()).map(|i| front[i]));
+        break;
+    }
+    chosen
+}
+
+//---------------------------------------------------------------------------------------//
+
+/*
+*   A legal coloring on the Pareto front, its colors renumbered to 1..=k
+*/
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ParetoPoint {
+    pub k: usize,
+    pub secondary: f64,
+    pub solution: Vec<usize>
+}
+
+/*
+*   The legal colorings found so far that no other legal coloring dominates in
+*   (number of colors, secondary objective), ordered by the number of colors
+*/
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ParetoFront {
+    pub points: Vec<ParetoPoint>
+}
+
+impl ParetoFront {
+    /*
+    *   Add a legal coloring unless a point of the front dominates or equals it,
+    *   points it dominates are removed. Returns true if it was added.
+    */
+    pub fn offer(&mut self, x: &Coloring, secondary: f64) -> bool {
+        let k = x.get_k();
+        let objectives = (k as f64, secondary);
+        if self.points.iter().any(|point| point.k == k && point.secondary == secondary || dominates((point.k as f64, point.secondary), objectives)) {
+            return false;
+        }
+        self.points.retain(|point| !dominates(objectives, (point.k as f64, point.secondary)));
+        let mut compact = x.clone();
+        compact.compact();
+        let position = self.points.partition_point(|point| point.k < k);
+        self.points.insert(position, ParetoPoint {k, secondary, solution: compact.solution});
+        true
+    }
+
+    pub fn write(&self, file_name: &str) {
+        fs::write(file_name, serde_json::to_string(&self.points).unwrap()).unwrap_or_else(|err| panic!("Could not write front {}: {}", file_name, err));
+    }
+}
+
+//---------------------------------------------------------------------------------------//
+
+/*
+*   Multi-objective DFPA. Every flower produces one offspring with the operators of its context, and the
+*   flowers of the next generation are chosen from the flowers and their offspring by non-dominated sorting
+*   and crowding distance on (conflicts, secondary objective), like NSGA-II. Legal colorings are offered to
+*   the Pareto front. Once the population contains a legal coloring for k it keeps evolving for polish
+*   generations, which gives the secondary objective time to improve, before continuing with k - 1.
+*/
+pub struct ParetoFpa {
+    contexts: Vec<FlowerContext>,
+    levy: LevyFlight,
+    parent_count: usize,
+    options: Parameters,
+    secondary: Secondary,
+    pub front: ParetoFront,
+    polish: usize,
+    // Generations evolved for k after the first legal coloring
+    polished: (usize, usize)
+}
+
+impl ParetoFpa {
+    pub fn new(pollinator: &Pollinator, n: usize, options: Parameters, secondary: Secondary, polish: usize) -> ParetoFpa {
+        let contexts = pollinator.contexts(n);
+        let parent_count = contexts.iter().map(|context| context.global.parents()).max().unwrap_or(1);
+        ParetoFpa {contexts, levy: LevyFlight::of(&options), parent_count, options, secondary, front: ParetoFront::default(), polish, polished: (0, 0)}
+    }
+}
+
+impl Evolution for ParetoFpa {
+    fn generation(&mut self, g: &Graph, pop: &mut [Coloring], best: &Coloring, k: usize, seed: u64, result: &mut RunResult) {
+        let ParetoFpa { contexts, levy, parent_count, options, secondary, front, .. } = self;
+        let parents = if *parent_count > 1 { best_flowers(pop, *parent_count) } else { vec![best.clone()] };
+
+        let generation = result.generations;
+        pop.par_iter().zip(contexts.par_iter_mut()).for_each(|(x, context)| {
+            let mut rng = rng_stream(seed, generation, x.index);
+            let offspring = &mut context.offspring;
+            offspring.copy_from(x);
+            if rng.gen_bool(options.switch_p) && *x != parents[0] {
+                let count = context.global.parents().min(parents.len());
+                context.global.apply(&mut rng, g, &parents[..count], x, offspring, k, levy);
+            } else {
+                context.local.apply(&mut rng, g, x, offspring, k, levy);
+            }
+        });
+        result.evaluations += pop.len();
+
+        // Offspring that equal their flower would only crowd the fronts
+        let pool: Vec<Coloring> = pop.iter().cloned()
+            .chain(pop.iter().zip(contexts.iter()).filter(|(x, context)| context.offspring.solution != x.solution).map(|(_, context)| context.offspring.clone()))
+            .collect();
+        let objectives: Vec<(f64, f64)> = pool.par_iter().map(|x| (x.tot_conflicts as f64, secondary.evaluate(x))).collect();
+        for (x, objective) in pool.iter().zip(&objectives) {
+            if x.tot_conflicts == 0 {
+                front.offer(x, objective.1);
+            }
+        }
+        for (i, chosen) in select(&objectives, pop.len()).into_iter().enumerate() {
+            pop[i].copy_from(&pool[chosen]);
+            pop[i].index = i;
+        }
+    }
+
+    fn accept(&mut self, pop: &[Coloring], k: usize) -> bool {
+        if self.polished.0 != k {
+            self.polished = (k, 0);
+        }
+        self.polished.1 += 1;
+        self.polished.1 > self.polish
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn pareto_fpa(g: &Graph, pollinator: &Pollinator, k: usize, options: Option<Parameters>, secondary: Secondary, polish: usize, stop: Option<usize>, budget: Budget, seed: u64) -> (RunResult, ParetoFront) {
+    let options = options.unwrap_or_else(Parameters::standard);
+    let mut evolution = ParetoFpa::new(pollinator, options.pop_size, options, secondary, polish);
+    let result = descend(g, &mut evolution, &mut SolverState::new(options.pop_size, k, seed), options, stop, budget, &mut ());
+    (result, evolution.front)
+}
+
+/*
+*   Trade off the number of colors against balance or weighted cost and print the Pareto front.
+*   The search starts at the DSATUR number of colors unless --k is given, so the front has a legal point.
+*   Usage: pareto <graph> [--objective balance|weighted] [--weights file] [--pollinator name] [--polish n] [--k n] [--seed s] [--out front.json]
+*/
+pub fn pareto_cli(args: &[String]) {
+    let objective: Objective = get_flag(args, "--objective").unwrap_or("balance").parse().unwrap_or_else(|err| panic!("{}", err));
+    let polish: usize = get_flag(args, "--polish").map_or(200, |value| value.parse().expect("Invalid number of polish generations"));
+    let pollinator = Pollinator::by_name(&pollinator_name(args)).unwrap_or_else(|err| panic!("{}", err));
+
+    solver_cli(args, "multi-objective DFPA", 0, || println!("Objectives: colors and {:?}, pollinator {}", objective, pollinator.name), |graph, k, seed| {
+        let secondary = match objective {
+            Objective::Balance => Secondary::balance(),
+            Objective::Weighted => Secondary::weighted(read_weights(get_flag(args, "--weights").expect("The weighted objective needs --weights"), graph.len()))
+        };
+        let (result, front) = pareto_fpa(graph, &pollinator, k, None, secondary, polish, None, Budget::standard(), seed);
+
+        let mut table = table!(["Colors", format!("{:?}", objective)]);
+        for point in &front.points {
+            table.add_row(row![point.k, point.secondary]);
+        }
+        table.printstd();
+        if let Some(file_name) = get_flag(args, "--out") {
+            front.write(file_name);
+            println!("Front written to {}", file_name);
+        }
+        result
+    });
+}
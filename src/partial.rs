@@ -0,0 +1,368 @@
+use super::structs::*;
+use super::levy::*;
+use super::*;
+
+use std::ops::Index;
+use serde::{Serialize, Deserialize};
+
+/*
+*   A legal coloring of part of the vertices (partial legal coloring), the other vertices are uncolored.
+*   Its cost is the number of uncolored vertices, a complete partial coloring is a legal coloring.
+*/
+#[derive(Clone, Debug)]
+pub struct PartialColoring {
+    pub index: usize,
+    // Color of every vertex, 0 if it is uncolored
+    pub solution: Vec<usize>,
+    // The uncolored vertices, with their position in the list for constant time updates
+    pub uncolored: Vec<usize>,
+    position: Vec<usize>,
+    pub lifetime: usize
+}
+
+impl PartialColoring {
+    /*
+    *   A partial coloring without any colored vertex
+    */
+    pub fn empty(index: usize, n: usize) -> PartialColoring {
+        PartialColoring {index, solution: vec![0; n], uncolored: (0..n).collect(), position: (0..n).collect(), lifetime: 0}
+    }
+
+    /*
+    *   Make a coloring legal by uncoloring conflicting vertices, those with the most conflicts first
+    */
+    pub fn from_coloring(x: &Coloring, g: &Graph) -> PartialColoring {
+        let mut partial = PartialColoring::empty(x.index, x.len());
+        partial.lifetime = x.lifetime;
+        for i in 0..x.len() {
+            partial.color(i, x[i]);
+        }
+        let mut conflicts = x.conflicts.clone();
+        for i in (0..x.len()).sorted_by_key(|&i| std::cmp::Reverse(x.conflicts[i])) {
+            if conflicts[i] == 0 {
+                continue;
+            }
+            for &neighbor in &g[i] {
+                if partial[neighbor] == partial[i] {
+                    conflicts[neighbor] -= 1;
+                }
+            }
+            partial.uncolor(i);
+        }
+        partial
+    }
+
+    /*
+    *   Complete the partial coloring, every uncolored vertex gets the color 1..=k used by the fewest
+    *   of its neighbors so far (the lowest on ties). The result has conflicts unless it finds a legal coloring.
+    */
+    pub fn to_coloring(&self, g: &Graph, k: usize) -> Coloring {
+        let mut solution = self.solution.clone();
+        let mut counts = vec![0; k + 1];
+        for &i in &self.uncolored {
+            counts.iter_mut().for_each(|count| *count = 0);
+            for &neighbor in &g[i] {
+                counts[solution[neighbor]] += 1;
+            }
+            solution[i] = (1..=k).min_by_key(|&color| counts[color]).unwrap();
+        }
+        let mut x = Coloring::from_vec(self.index, solution, g);
+        x.lifetime = self.lifetime;
+        x
+    }
+
+    pub fn len(&self) -> usize {
+        self.solution.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solution.is_empty()
+    }
+
+    /*
+    *   Number of uncolored vertices
+    */
+    pub fn cost(&self) -> usize {
+        self.uncolored.len()
+    }
+
+    /*
+    *   Give vertex i a color (or 0), without looking at its neighbors
+    */
+    pub fn color(&mut self, i: usize, color: usize) {
+        if color == 0 {
+            return self.uncolor(i);
+        }
+        if self.solution[i] == 0 {
+            let last = *self.uncolored.last().unwrap();
+            self.uncolored.swap_remove(self.position[i]);
+            self.position[last] = self.position[i];
+            self.position[i] = usize::MAX;
+        }
+        self.solution[i] = color;
+    }
+
+    pub fn uncolor(&mut self, i: usize) {
+        if self.solution[i] == 0 {
+            return;
+        }
+        self.solution[i] = 0;
+        self.position[i] = self.uncolored.len();
+        self.uncolored.push(i);
+    }
+
+    /*
+    *   Give vertex i a color and uncolor its neighbors of that color, which keeps the coloring legal
+    */
+    pub fn assign(&mut self, i: usize, color: usize, g: &Graph) {
+        for &neighbor in &g[i] {
+            if self.solution[neighbor] == color {
+                self.uncolor(neighbor);
+            }
+        }
+        self.color(i, color);
+    }
+
+    /*
+    *   Turn this partial coloring into a copy of other, reusing the allocated vectors
+    */
+    pub fn copy_from(&mut self, other: &PartialColoring) {
+        self.index = other.index;
+        self.solution.clone_from(&other.solution);
+        self.uncolored.clone_from(&other.uncolored);
+        self.position.clone_from(&other.position);
+        self.lifetime = other.lifetime;
+    }
+}
+
+impl Index<usize> for PartialColoring {
+    type Output = usize;
+
+    fn index(&self, index: usize) -> &usize {
+        &self.solution[index]
+    }
+}
+
+//---------------------------------------------------------------------------------------//
+
+/*
+*   Tabu search in the space of partial legal colorings (PartialCol, Blöchliger & Zufferey 2008) for a fixed
+*   number of iterations. Every iteration gives an uncolored vertex the color that uncolors the fewest
+*   neighbors, those neighbors may not get the color back for 0.6 * (uncolored vertices) + rand(0..10)
+*   iterations unless that beats the best partial coloring found so far. The best partial coloring found
+*   is written to offspring.
+*/
+pub struct PartialCol {
+    pub iterations: usize,
+    // Number of neighbors of every vertex with every color
+    gamma: Vec<usize>,
+    // Iteration until which a color is tabu for a vertex
+    tabu: Vec<usize>,
+    current: PartialColoring
+}
+
+impl PartialCol {
+    pub fn new(iterations: usize) -> PartialCol {
+        PartialCol { iterations, gamma: Vec::new(), tabu: Vec::new(), current: PartialColoring::empty(0, 0) }
+    }
+
+    pub fn standard() -> PartialCol {
+        PartialCol::new(100)
+    }
+
+    pub fn apply(&mut self, rng: &mut Rand, g: &Graph, x: &PartialColoring, offspring: &mut PartialColoring, k: usize) {
+        let n = g.len();
+        let colors = k + 1;
+        let PartialCol { gamma, tabu, current, .. } = self;
+        gamma.clear();
+        gamma.resize(n * colors, 0);
+        for i in 0..n {
+            for &neighbor in &g[i] {
+                gamma[i * colors + x[neighbor]] += 1;
+            }
+        }
+        tabu.clear();
+        tabu.resize(n * colors, 0);
+        current.copy_from(x);
+        let mut best_cost = x.cost();
+
+        for it in 1..=self.iterations {
+            if current.cost() == 0 {
+                break;
+            }
+            let mut chosen = None;
+            let mut best_delta = usize::MAX;
+            let mut ties = 0;
+            for &i in &current.uncolored {
+                for color in 1..=k {
+                    // Coloring i uncolors its neighbors of that color
+                    let delta = gamma[i * colors + color];
+                    if tabu[i * colors + color] >= it && current.cost() + delta > best_cost {
+                        continue;
+                    }
+                    if delta < best_delta {
+                        best_delta = delta;
+                        chosen = Some((i, color));
+                        ties = 1;
+                    } else if delta == best_delta {
+                        // Reservoir sampling over the tied moves
+                        ties += 1;
+                        if rng.gen_range(0..ties) == 0 {
+                            chosen = Some((i, color));
+                        }
+                    }
+                }
+            }
+            let Some((i, color)) = chosen else {
+                break;
+            };
+            let tenure = (0.6 * current.cost() as f64) as usize;
+            for &neighbor in &g[i] {
+                if current[neighbor] != color {
+                    continue;
+                }
+                current.uncolor(neighbor);
+                for &next in &g[neighbor] {
+                    gamma[next * colors + color] -= 1;
+                    gamma[next * colors] += 1;
+                }
+                tabu[neighbor * colors + color] = it + tenure + rng.gen_range(0..10);
+            }
+            current.color(i, color);
+            for &neighbor in &g[i] {
+                gamma[neighbor * colors] -= 1;
+                gamma[neighbor * colors + color] += 1;
+            }
+            if current.cost() < best_cost {
+                best_cost = current.cost();
+                offspring.copy_from(current);
+            }
+        }
+        offspring.index = x.index;
+        offspring.lifetime = x.lifetime;
+    }
+}
+
+//---------------------------------------------------------------------------------------//
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PartialParameters {
+    // PartialCol iterations of every abiotic pollination
+    pub tabu_iterations: usize
+}
+
+impl PartialParameters {
+    pub fn standard() -> PartialParameters {
+        PartialParameters {
+            tabu_iterations: 100
+        }
+    }
+}
+
+impl Default for PartialParameters {
+    fn default() -> PartialParameters {
+        PartialParameters::standard()
+    }
+}
+
+/*
+*   DFPA on partial legal colorings, minimizing the number of uncolored vertices. Biotic pollination
+*   copies a Lévy distributed number of colors of the best flower and uncolors the neighbors they conflict
+*   with, abiotic pollination runs PartialCol. The population seen by descend holds the flowers completed
+*   with to_coloring, so k goes down as soon as a flower has no uncolored vertex left (or its completion is legal).
+*/
+pub struct PartialFpa {
+    flowers: Vec<PartialColoring>,
+    offspring: Vec<PartialColoring>,
+    searches: Vec<PartialCol>,
+    settings: PartialParameters,
+    levy: LevyFlight,
+    options: Parameters
+}
+
+impl PartialFpa {
+    pub fn new(settings: PartialParameters, options: Parameters) -> PartialFpa {
+        PartialFpa {flowers: Vec::new(), offspring: Vec::new(), searches: Vec::new(), settings, levy: LevyFlight::of(&options), options}
+    }
+
+    fn reset(&mut self, g: &Graph, pop: &[Coloring]) {
+        self.flowers = pop.iter().map(|x| PartialColoring::from_coloring(x, g)).collect();
+        self.offspring = self.flowers.clone();
+        self.searches = pop.iter().map(|_| PartialCol::new(self.settings.tabu_iterations)).collect();
+    }
+}
+
+impl Evolution for PartialFpa {
+    fn populate(&mut self, g: &Graph, n: usize, k: usize, seed: u64, generation: usize) -> Vec<Coloring> {
+        let pop = g.populate(&mut rng_stream(seed, generation, POPULATE_STREAM), n, k);
+        self.reset(g, &pop);
+        self.flowers.iter().map(|x| x.to_coloring(g, k)).collect()
+    }
+
+    fn generation(&mut self, g: &Graph, pop: &mut [Coloring], best: &Coloring, k: usize, seed: u64, result: &mut RunResult) {
+        // A population that was not drawn by populate, e.g. after resuming
+        if self.flowers.len() != pop.len() {
+            self.reset(g, pop);
+        }
+        let PartialFpa { flowers, offspring, searches, levy, options, .. } = self;
+        let best = flowers.iter().min_by_key(|x| x.cost()).unwrap().clone();
+        let Parameters { switch_p, lifetime_limit, .. } = *options;
+
+        let generation = result.generations;
+        let restarts = flowers.par_iter_mut().zip(offspring.par_iter_mut()).zip(searches.par_iter_mut()).zip(pop.par_iter_mut())
+            .map(|(((x, x_new), search), completed)| {
+                let mut rng = rng_stream(seed, generation, x.index);
+                let p = rng.gen_bool(switch_p);
+                let restarted = x.lifetime >= lifetime_limit && p && x.index != best.index;
+                if restarted {
+                    *x = PartialColoring::from_coloring(&Coloring::new(x.index, g, k, &mut rng), g);
+                    x.lifetime = 0;
+                } else {
+                    x_new.copy_from(x);
+                    if p && x.index != best.index {
+                        // Biotic pollination
+                        let cutoff = levy.sample(&mut rng, best.len());
+                        for i in index::sample(&mut rng, best.len(), cutoff) {
+                            if best[i] > 0 {
+                                x_new.assign(i, best[i], g);
+                            }
+                        }
+                    } else {
+                        // Abiotic pollination
+                        search.apply(&mut rng, g, x, x_new, k);
+                    }
+                    if x_new.cost() <= x.cost() {
+                        x_new.lifetime = if x_new.cost() == x.cost() { x.lifetime + 1 } else { 0 };
+                        std::mem::swap(x, x_new);
+                    } else {
+                        x.lifetime += 1;
+                    }
+                }
+                *completed = x.to_coloring(g, k);
+                restarted
+            }).filter(|&restarted| restarted).count();
+        result.evaluations += pop.len();
+        result.restarts += restarts;
+    }
+}
+
+pub fn partial_fpa(g: &Graph, k: usize, options: Option<Parameters>, settings: PartialParameters, stop: Option<usize>, budget: Budget, seed: u64) -> RunResult {
+    let options = options.unwrap_or_else(Parameters::standard);
+    descend(g, &mut PartialFpa::new(settings, options), &mut SolverState::new(options.pop_size, k, seed), options, stop, budget, &mut ())
+}
+
+/*
+*   Run DFPA on partial legal colorings of a graph.
+*   Usage: partial <graph> [--tabu-iterations n] [--k n] [--seed s]
+*/
+pub fn partial_cli(args: &[String]) {
+    let mut settings = PartialParameters::standard();
+    if let Some(value) = get_flag(args, "--tabu-iterations") {
+        settings.tabu_iterations = value.parse().expect("Invalid number of tabu iterations");
+    }
+
+    solver_cli(args, "partial DFPA", 1, || println!("PartialCol iterations: {}", settings.tabu_iterations), |graph, k, seed| {
+        partial_fpa(graph, k, None, settings, None, Budget::standard(), seed)
+    });
+}
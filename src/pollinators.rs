@@ -1,106 +1,62 @@
 use super::structs::*;
+use super::registry::*;
+use super::levy::*;
 use super::*;
 //use rand_distr::Uniform;
-use rand::prelude::ThreadRng;
-use itertools::Itertools;
+use rand::seq::SliceRandom;
 
-pub trait Pollinator {
-    fn global(rng: &mut ThreadRng, g: &Graph, best: &Coloring, other: &Coloring, lambda: f32) -> Coloring;
-    fn local(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring;
-}
-
-pub struct CM;
-
-impl Pollinator for CM {
-    fn global(rng: &mut ThreadRng, g: &Graph, best: &Coloring, other: &Coloring, lambda: f32) -> Coloring {
-        levy_pop1(g, rng, best, other, lambda)
-    }
-    fn local(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring {
-        change_multiple(rng, g, x, k, lambda)           // Seems best
+pub fn levy_pop1(rng: &mut Rand, g: &Graph, best: &Coloring, other: &Coloring, offspring: &mut Coloring, levy: &LevyFlight, sample: &mut Vec<usize>) {
+    let n = best.len();
+    let cutoff = levy.sample(rng, n);
+    let indices = sample_nodes(rng, n, cutoff, sample);
+    for &i in indices {
+        offspring[i] = best[i];
     }
+    
+    offspring.update_multiple_conflicts(indices, other, g);
 }
 
-pub struct CMB;
-
-impl Pollinator for CMB {
-    fn global(rng: &mut ThreadRng, g: &Graph, best: &Coloring, other: &Coloring, lambda: f32) -> Coloring {
-        levy_pop1(g, rng, best, other, lambda)
-    }
-    fn local(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring {
-        change_multiple_best(rng, g, x, k, lambda)    // Seems pretty good
-    }
+/*
+*   levy_pop1 with the buffer it samples nodes into
+*/
+#[derive(Default)]
+pub struct LevyPop1 {
+    sample: Vec<usize>
 }
 
-pub struct CA;
-
-impl Pollinator for CA {
-    fn global(rng: &mut ThreadRng, g: &Graph, best: &Coloring, other: &Coloring, lambda: f32) -> Coloring {
-        levy_pop1(g, rng, best, other, lambda)
-    }
-    fn local(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring {
-        change_all_critical_opt(g, x, k)     // Seems pretty good
+impl GlobalOperator for LevyPop1 {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, parents: &[Coloring], other: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight) {
+        levy_pop1(rng, g, &parents[0], other, offspring, levy, &mut self.sample)
     }
 }
 
-pub struct CMW;
-
-impl Pollinator for CMW {
-    fn global(rng: &mut ThreadRng, g: &Graph, best: &Coloring, other: &Coloring, lambda: f32) -> Coloring {
-        levy_pop1(g, rng, best, other, lambda)
-    }
-    fn local(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring {
-        change_multiple_worst(rng, g, x, k, lambda)   // Seems ok
+pub fn levy_circ(rng: &mut Rand, g: &Graph, x: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
+    let n = x.len();
+    let cutoff = levy.sample(rng, n);
+    let indices = sample_nodes(rng, n, cutoff, &mut scratch.sample);
+    for (&i, &j) in indices.iter().zip(indices.iter().skip(1)) {
+        offspring[i] = offspring[j];
     }
-}
-
-
-/*
-*   Generate a random number from a levy distribution
-*/
-fn levy(rng: &mut ThreadRng, c: f32) -> f32 {
-    let n: f32 = rng.sample(StandardNormal);
-    c / n.powf(2.0)
+    offspring.update_multiple_conflicts(indices, x, g);
 }
 
 /*
-*   Sample from levy until a value in the given range is found
+*   Draw amount distinct nodes of 0..n by a partial Fisher-Yates shuffle of the buffer. The buffer keeps
+*   a permutation of 0..n between calls, so a sample takes O(amount) time and allocates nothing.
 */
-fn adjusted_levy(rng: &mut ThreadRng, limit: usize, c: f32) -> usize {
-    loop {
-        let sample = levy(rng, c).round() as usize;
-        if sample < limit {
-            return sample;
-        }
+pub fn sample_nodes<'a>(rng: &mut Rand, n: usize, amount: usize, buffer: &'a mut Vec<usize>) -> &'a [usize] {
+    if buffer.len() != n {
+        buffer.clear();
+        buffer.extend(0..n);
     }
-}
-
-fn levy_pop1(g: &Graph, rng: &mut ThreadRng, best: &Coloring, other: &Coloring, lambda: f32) -> Coloring {
-    let n = best.len();
-    let mut offspring = other.clone();
-    let cutoff = adjusted_levy(rng, n, lambda);
-    let indices = index::sample(rng, n, cutoff).into_vec();
-    for &i in &indices {
-        offspring[i] = best[i];
-    }
-    
-    offspring.update_multiple_conflicts(&indices, other, g);
-
-    offspring
-}
-
-fn levy_circ(g: &Graph, rng: &mut ThreadRng, x: &Coloring, lambda: f32) -> Coloring {
-    let n = x.len();
-    let mut offspring = x.clone();
-    let cutoff = adjusted_levy(rng, n, lambda);
-    let indices = index::sample(rng, n, cutoff);
-    for (i, j) in indices.iter().zip(indices.iter().skip(1)) {
-        offspring[i] = offspring[j];
+    for i in 0..amount {
+        let j = rng.gen_range(i..n);
+        buffer.swap(i, j);
     }
-    offspring.update_multiple_conflicts(&indices.into_vec(), x, g);
-    offspring
+    &buffer[..amount]
 }
 
-//pub fn partial_swap(rng: &mut ThreadRng, g: &Graph, x: &Coloring) -> Coloring {
+//pub fn partial_swap(rng: &mut Rand, g: &Graph, x: &Coloring) -> Coloring {
 //    let n = x.len();
 //    let generator = Uniform::new(0, n);
 //    let mut new = x.clone();
@@ -119,150 +75,291 @@ fn levy_circ(g: &Graph, rng: &mut ThreadRng, x: &Coloring, lambda: f32) -> Color
 //    new
 //}
 
+/*
+*   Buffers reused by the local operators of a flower between generations
+*/
+#[derive(Default)]
+pub struct Scratch {
+    pub counts: Vec<usize>,
+    pub nodes: Vec<usize>,
+    pub colors: Vec<usize>,
+    // A permutation of the nodes for sample_nodes
+    pub sample: Vec<usize>
+}
+
+/*
+*   A local operator function together with its own scratch buffers
+*/
+pub struct WithScratch<F> {
+    operator: F,
+    scratch: Scratch
+}
+
+pub type LocalFn = fn(&mut Rand, &Graph, &Coloring, &mut Coloring, usize, &LevyFlight, &mut Scratch);
+
+pub fn with_scratch(operator: LocalFn) -> Box<dyn LocalOperator> {
+    Box::new(WithScratch { operator, scratch: Scratch::default() })
+}
+
+impl LocalOperator for WithScratch<LocalFn> {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, x: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight) {
+        (self.operator)(rng, g, x, offspring, k, levy, &mut self.scratch)
+    }
+}
+
+/*
+*   Put the nodes with conflicts into the scratch buffer
+*/
+fn collect_critical(x: &Coloring, nodes: &mut Vec<usize>) {
+    nodes.clear();
+    nodes.extend((0..x.len()).filter(|&i| x.conflicts[i] != 0));
+}
+
 // needs full graph
-fn change_worst(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize) -> Coloring {
+pub fn change_worst(rng: &mut Rand, g: &Graph, x: &Coloring, new: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
     //let worst = (0..g.len()).max_by_key(|&from| g[from].iter().filter(|&&to| x.solution[from] == x.solution[to]).count()).unwrap();
     let worst = (0..g.len()).max_by_key(|&from| x.conflicts[from]).unwrap();
 
-    let neighbor_colors = (1..=k).chain(g[worst].iter().map(|&neighbor| x[neighbor])).counts();
-    let best_color = neighbor_colors.iter().min_by_key(|&(_, count)| count).unwrap().0;
-    let mut new = x.clone();
-    new[worst] = *best_color;
+    new[worst] = least_conflicting_color(rng, g, x, worst, k, &mut scratch.counts);
 
     // Efficiently calculate new conflicts
     new.update_conflicts(worst, x, g);
-    new
 }
 
 // needs full graph
-fn change_multiple_worst(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring {
-    let mut new = x.clone();
-    let worst = (0..g.len()).filter(|&i| x.conflicts[i] != 0).sorted_unstable_by_key(|&from| x.conflicts[from]).rev();
-    let n = adjusted_levy(rng, x.len(), lambda);
-    for i in worst.take(n) {
-        let neighbor_colors = (1..=k).chain(g[i].iter().map(|&neighbor| x[neighbor])).counts();
-        let best_color = neighbor_colors.iter().min_by_key(|&(_, count)| count).unwrap().0;
-        new[i] = *best_color;
+pub fn change_multiple_worst(rng: &mut Rand, g: &Graph, x: &Coloring, new: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
+    collect_critical(x, &mut scratch.nodes);
+    scratch.nodes.sort_unstable_by_key(|&i| std::cmp::Reverse(x.conflicts[i]));
+    let n = levy.sample(rng, x.len());
+    scratch.nodes.truncate(n);
+    for &i in &scratch.nodes {
+        new[i] = least_conflicting_color(rng, g, x, i, k, &mut scratch.counts);
     }
-    new
+    new.update_multiple_conflicts(&scratch.nodes, x, g);
 }
 
 // needs full graph
-fn change_multiple_best(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring {
-    let mut new = x.clone();
-    let best = (0..g.len()).filter(|&i| x.conflicts[i] != 0).sorted_unstable_by_key(|&i| x.conflicts[i]);
-    let n = adjusted_levy(rng, x.len(), lambda);
-
-    let indices: Vec<usize> = best.take(n).collect();
-
-    for &i in &indices {
-        let neighbor_colors = (1..=k).chain(g[i].iter().map(|&neighbor| new[neighbor])).counts();
-        let best_color = neighbor_colors.iter().min_by_key(|&(_, count)| count).unwrap().0;
-        new[i] = *best_color;
+pub fn change_multiple_best(rng: &mut Rand, g: &Graph, x: &Coloring, new: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
+    collect_critical(x, &mut scratch.nodes);
+    scratch.nodes.sort_unstable_by_key(|&i| x.conflicts[i]);
+    let n = levy.sample(rng, x.len());
+    scratch.nodes.truncate(n);
+
+    for &i in &scratch.nodes {
+        new[i] = least_conflicting_color(rng, g, new, i, k, &mut scratch.counts);
     }
 
-    new.update_multiple_conflicts(&indices, x, g);
-
-    new
+    new.update_multiple_conflicts(&scratch.nodes, x, g);
 }
 
 // needs full graph
-fn try_change_critical(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize) -> Coloring {
-    let mut new = x.clone();
-
-    let critical: Vec<usize> = (0..g.len()).filter(|&i| x.conflicts[i] != 0).collect();
-    let generator = Uniform::new(0, critical.len());
+pub fn try_change_critical(rng: &mut Rand, g: &Graph, x: &Coloring, new: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
+    collect_critical(x, &mut scratch.nodes);
+    if scratch.nodes.is_empty() {
+        return;
+    }
+    let generator = Uniform::new(0, scratch.nodes.len());
     let i = rng.sample(generator);
-    let i = critical[i];
-    let neighbor_colors = (1..=k).chain(g[i].iter().map(|&neighbor| new[neighbor])).counts();
-    let best_color = neighbor_colors.iter().min_by_key(|&(_, count)| count).unwrap().0;
-    new[i] = *best_color;
+    let i = scratch.nodes[i];
+    new[i] = least_conflicting_color(rng, g, new, i, k, &mut scratch.counts);
 
     new.update_conflicts(i, x, g);
-
-    new
 }
 
 // needs full graph
-fn change_one(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize) -> Coloring {
-    let mut new = x.clone();
-    let critical = (0..g.len()).filter(|&i| x.conflicts[i] != 0);
-    for i in critical {
-        let neighbor_colors = (1..=k).chain(g[i].iter().map(|&neighbor| new[neighbor])).counts();
-        let (&best_color, &conflicts) = neighbor_colors.iter().min_by_key(|&(_, count)| count).unwrap();
-        if let Some((&color, _)) = neighbor_colors.iter().find(|&(_, &count)| count == 1) {
+pub fn change_one(rng: &mut Rand, g: &Graph, x: &Coloring, new: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
+    collect_critical(x, &mut scratch.nodes);
+    for &i in &scratch.nodes {
+        color_counts(g, new, i, k, &mut scratch.counts);
+        scratch.colors.clear();
+        scratch.colors.extend((1..=k).filter(|&color| scratch.counts[color] == 0));
+        if let Some(&color) = scratch.colors.choose(rng) {
             new[i] = color;
             new.update_conflicts(i, x, g);
             break;
         }
     }
-    new
 }
 
 // needs full graph
-fn change_multiple(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring {
-    let mut new = x.clone();
+pub fn change_multiple(rng: &mut Rand, g: &Graph, x: &Coloring, new: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
     //let best = (0..g.len()).map(|from| x.conflicts[from]).enumerate().filter(|&(_, conflicts)| conflicts != 0).sorted_unstable_by_key(|&(_, conflicts)| conflicts);
-    let best = (0..g.len()).filter(|&i| x.conflicts[i] != 0);
-    let n = adjusted_levy(rng, x.len(), lambda);
-
-    let indices: Vec<usize> = best.take(n).collect();
+    collect_critical(x, &mut scratch.nodes);
+    let n = levy.sample(rng, x.len());
+    scratch.nodes.truncate(n);
 
-    for &i in &indices {
-        let neighbor_colors = (1..=k).chain(g[i].iter().map(|&neighbor| new[neighbor])).counts();
-        let best_color = neighbor_colors.iter().min_by_key(|&(_, count)| count).unwrap().0;
-        new[i] = *best_color;
+    for &i in &scratch.nodes {
+        new[i] = least_conflicting_color(rng, g, new, i, k, &mut scratch.counts);
     }
 
-    new.update_multiple_conflicts(&indices, x, g);
+    new.update_multiple_conflicts(&scratch.nodes, x, g);
+}
+
+// needs full graph
+pub fn change_all_critical(rng: &mut Rand, g: &Graph, x: &Coloring, new: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
+    collect_critical(x, &mut scratch.nodes);
+    for &i in &scratch.nodes {
+        new[i] = least_conflicting_color(rng, g, new, i, k, &mut scratch.counts);
+    }
 
-    new
+    new.update_multiple_conflicts(&scratch.nodes, x, g);
 }
 
 // needs full graph
-fn change_all_critical(g: &Graph, x: &Coloring, k: usize) -> Coloring {
-    let mut new = x.clone();
-    let best: Vec<usize> = (0..g.len()).filter(|&i| x.conflicts[i] != 0).collect();
-    for &i in &best {
-        let neighbor_colors = (1..=k).chain(g[i].iter().map(|&neighbor| new[neighbor])).counts();
-        let best_color = neighbor_colors.iter().min_by_key(|&(_, count)| count).unwrap().0;
-        new[i] = *best_color;
+pub fn change_all_critical_opt(rng: &mut Rand, g: &Graph, x: &Coloring, new: &mut Coloring, k: usize, levy: &LevyFlight, scratch: &mut Scratch) {
+    collect_critical(new, &mut scratch.nodes);
+
+    for &i in &scratch.nodes {
+        new[i] = least_conflicting_color(rng, g, new, i, k, &mut scratch.counts);
     }
 
-    new.update_multiple_conflicts(&best, x, g);
+    new.update_multiple_conflicts(&scratch.nodes, x, g);
+}
 
-    new
+/*
+*   Tabu search (Tabucol, Hertz & de Werra 1987) for a fixed number of iterations. Every iteration
+*   recolors a conflicting node with the best color that is not tabu, a node may not get its old
+*   color back for 0.6 * (conflicting nodes) + rand(0..10) iterations unless that beats the best
+*   coloring found so far. The best coloring found is written to offspring.
+*/
+pub struct Tabucol {
+    pub iterations: usize,
+    // Number of neighbors of every node with every color
+    gamma: Vec<usize>,
+    // Iteration until which a color is tabu for a node
+    tabu: Vec<usize>,
+    current: Vec<usize>
 }
 
-// needs full graph
-fn change_all_critical_opt(g: &Graph, x: &Coloring, k: usize) -> Coloring {
-    let mut new = x.clone();
-    let critical: Vec<usize> = (0..g.len()).filter(|&i| new.conflicts[i] != 0).collect();
-
-    for &i in &critical {
-        let neighbor_colors = (1..=k).chain(g[i].iter().map(|&neighbor| new[neighbor])).counts();
-        let mut best_count =  usize::MAX;
-        let mut best_color = 0;
-        for (color, count) in neighbor_colors {
-            if count == 1 {
-                best_color = color;
+impl Tabucol {
+    pub fn new(iterations: usize) -> Tabucol {
+        Tabucol { iterations, gamma: Vec::new(), tabu: Vec::new(), current: Vec::new() }
+    }
+
+    pub fn standard() -> Tabucol {
+        Tabucol::new(100)
+    }
+}
+
+impl LocalOperator for Tabucol {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, x: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight) {
+        let n = g.len();
+        let colors = k + 1;
+        debug_assert!(x.solution.iter().all(|&color| color <= k), "Tabucol got colors above k = {}", k);
+        let Tabucol { gamma, tabu, current, .. } = self;
+        gamma.clear();
+        gamma.resize(n * colors, 0);
+        for i in 0..n {
+            for &neighbor in &g[i] {
+                gamma[i * colors + x[neighbor]] += 1;
+            }
+        }
+        tabu.clear();
+        tabu.resize(n * colors, 0);
+        current.clone_from(&x.solution);
+        let mut conflicts = x.tot_conflicts as i64;
+        let mut best_conflicts = conflicts;
+        let mut improved = false;
+
+        for it in 1..=self.iterations {
+            if best_conflicts == 0 {
                 break;
-            } else if count < best_count {
-                best_count = count;
-                best_color = color;
             }
+            let mut chosen = None;
+            let mut best_delta = i64::MAX;
+            let mut ties = 0;
+            let mut critical = 0;
+            for i in 0..n {
+                let own = gamma[i * colors + current[i]] as i64;
+                if own == 0 {
+                    continue;
+                }
+                critical += 1;
+                for color in 1..=k {
+                    if color == current[i] {
+                        continue;
+                    }
+                    let delta = gamma[i * colors + color] as i64 - own;
+                    if tabu[i * colors + color] >= it && conflicts + delta >= best_conflicts {
+                        continue;
+                    }
+                    if delta < best_delta {
+                        best_delta = delta;
+                        chosen = Some((i, color));
+                        ties = 1;
+                    } else if delta == best_delta {
+                        // Reservoir sampling over the tied moves
+                        ties += 1;
+                        if rng.gen_range(0..ties) == 0 {
+                            chosen = Some((i, color));
+                        }
+                    }
+                }
+            }
+            let Some((i, color)) = chosen else {
+                break;
+            };
+            let old = current[i];
+            current[i] = color;
+            for &neighbor in &g[i] {
+                gamma[neighbor * colors + old] -= 1;
+                gamma[neighbor * colors + color] += 1;
+            }
+            conflicts += best_delta;
+            tabu[i * colors + old] = it + (0.6 * critical as f64) as usize + rng.gen_range(0..10);
+            if conflicts < best_conflicts {
+                best_conflicts = conflicts;
+                offspring.solution.copy_from_slice(current);
+                improved = true;
+            }
+        }
+        if improved {
+            offspring.recompute_conflicts(g);
+        }
+    }
+}
+
+/*
+*   Count how many neighbors of node i use each color 1..=k
+*/
+fn color_counts(g: &Graph, x: &Coloring, i: usize, k: usize, counts: &mut Vec<usize>) {
+    counts.clear();
+    counts.resize(k + 1, 0);
+    for &neighbor in &g[i] {
+        if x[neighbor] <= k {
+            counts[x[neighbor]] += 1;
         }
-        new[i] = best_color;
     }
+}
 
-    new.update_multiple_conflicts(&critical, x, g);
-    new
+/*
+*   Find the color 1..=k used by the fewest neighbors of node i, ties are broken randomly
+*/
+fn least_conflicting_color(rng: &mut Rand, g: &Graph, x: &Coloring, i: usize, k: usize, counts: &mut Vec<usize>) -> usize {
+    color_counts(g, x, i, k, counts);
+    let mut best_color = 1;
+    let mut best_count = usize::MAX;
+    let mut ties = 0;
+    for (color, &count) in counts.iter().enumerate().skip(1) {
+        if count < best_count {
+            best_color = color;
+            best_count = count;
+            ties = 1;
+        } else if count == best_count {
+            // Reservoir sampling over the tied colors
+            ties += 1;
+            if rng.gen_range(0..ties) == 0 {
+                best_color = color;
+            }
+        }
+    }
+    best_color
 }
 
 //pub struct Mox;
 //
 //impl Pollinator for Mox {
-//    fn global(rng: &mut ThreadRng, _g: &Graph, best: &Coloring, other: &Coloring) -> Coloring {
+//    fn global(rng: &mut Rand, _g: &Graph, best: &Coloring, other: &Coloring) -> Coloring {
 //        let n = best.len();
 //        let mut offspring = Vec::new();
 //        let prob = 0.1;
@@ -277,7 +374,7 @@ fn change_all_critical_opt(g: &Graph, x: &Coloring, k: usize) -> Coloring {
 //        //std::cmp::min_by_key(offspring1, offspring2, |x| conflicts(g, x))
 //    }
 //
-//    fn local(rng: &mut ThreadRng, _g: &Graph, x: &Coloring, _k: usize) -> Coloring {
+//    fn local(rng: &mut Rand, _g: &Graph, x: &Coloring, _k: usize) -> Coloring {
 //        let n = x.len();
 //        // let mut rng = thread_rng();
 //        let mut offspring = x.clone();
@@ -293,7 +390,7 @@ fn change_all_critical_opt(g: &Graph, x: &Coloring, k: usize) -> Coloring {
 //pub struct MIS;
 //
 //impl Pollinator for MIS {
-//    fn global(rng: &mut ThreadRng, g: &Graph, best: &Coloring, other: &Coloring, lambda: f32) -> Coloring {
+//    fn global(rng: &mut Rand, g: &Graph, best: &Coloring, other: &Coloring, levy: &LevyFlight) -> Coloring {
 //        let n = best.len();
 //        //let parent1 = best.iter().enumerate().into_group_map_by(|(node, color)| color);
 //        //let parent2 = other.iter().enumerate().into_group_map_by(|(node, color)| color);
@@ -338,7 +435,7 @@ fn change_all_critical_opt(g: &Graph, x: &Coloring, k: usize) -> Coloring {
 //        Coloring::from_vec(child1, g)
 //    }
 //
-//    fn local(rng: &mut ThreadRng, g: &Graph, x: &Coloring, k: usize, lambda: f32) -> Coloring {
+//    fn local(rng: &mut Rand, g: &Graph, x: &Coloring, k: usize, levy: &LevyFlight) -> Coloring {
 //        change_worst(rng, g, x, k)
 //        //partial_swap(rng, g, x)
 //        //levy_circ(rng, x)
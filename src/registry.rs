@@ -0,0 +1,218 @@
+use super::structs::*;
+use super::pollinators::*;
+use super::levy::*;
+use super::crossover::*;
+use super::control::*;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/*
+*   Biotic (global) pollination: move a flower towards the best flowers. The offspring starts as a copy
+*   of other and is modified in place.
+*/
+pub trait GlobalOperator: Send {
+    /*
+    *   The number of best flowers of the population the operator is given, best first
+    */
+    fn parents(&self) -> usize {
+        1
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, parents: &[Coloring], other: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight);
+}
+
+/*
+*   Abiotic (local) pollination: improve a flower on its own. The offspring starts as a copy of x
+*   and is modified in place. Every flower has its own operator objects, so they can keep state.
+*/
+pub trait LocalOperator: Send {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, x: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight);
+
+    /*
+    *   How likely each of the operators combined by this operator is chosen, empty for a single operator
+    */
+    fn selection(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    /*
+    *   What an adaptive operator learned during the run, empty for other operators
+    */
+    fn qualities(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    /*
+    *   Continue with the qualities of a resumed run
+    */
+    fn restore_qualities(&mut self, qualities: &[f64]) {}
+}
+
+impl<F> GlobalOperator for F where F: FnMut(&mut Rand, &Graph, &Coloring, &Coloring, &mut Coloring, &LevyFlight) + Send {
+    fn apply(&mut self, rng: &mut Rand, g: &Graph, parents: &[Coloring], other: &Coloring, offspring: &mut Coloring, k: usize, levy: &LevyFlight) {
+        self(rng, g, &parents[0], other, offspring, levy)
+    }
+}
+
+/*
+*   A global and a local operator used together by the solvers
+*/
+pub struct Pollinator {
+    pub name: String,
+    pub global: GlobalFactory,
+    pub local: LocalFactory
+}
+
+impl Pollinator {
+    /*
+    *   Look up a pollinator in the registry, either a registered pollinator like "CA"
+    *   or a combination of operators like "levy_pop1+tabucol". Local operators separated by
+    *   slashes are chosen adaptively, e.g. "levy_pop1+tabucol/change_all_critical_opt".
+    */
+    pub fn by_name(name: &str) -> Result<Pollinator, String> {
+        registry().read().unwrap().pollinator(name)
+    }
+
+    /*
+    *   Fresh operators and an offspring buffer for one flower
+    */
+    pub fn context(&self) -> FlowerContext {
+        FlowerContext { global: (self.global)(), local: (self.local)(), offspring: Coloring::empty(0, 0) }
+    }
+
+    pub fn contexts(&self, n: usize) -> Vec<FlowerContext> {
+        (0..n).map(|_| self.context()).collect()
+    }
+}
+
+/*
+*   The operators of a single flower and the buffer its offspring is written to, kept between generations
+*/
+pub struct FlowerContext {
+    pub global: Box<dyn GlobalOperator>,
+    pub local: Box<dyn LocalOperator>,
+    pub offspring: Coloring
+}
+
+pub type GlobalFactory = Arc<dyn Fn() -> Box<dyn GlobalOperator> + Send + Sync>;
+pub type LocalFactory = Arc<dyn Fn() -> Box<dyn LocalOperator> + Send + Sync>;
+
+/*
+*   Operators and pollinators by name. Operators are registered as factories, so every solver run
+*   gets operator objects of its own.
+*/
+pub struct Registry {
+    globals: BTreeMap<String, GlobalFactory>,
+    locals: BTreeMap<String, LocalFactory>,
+    pollinators: BTreeMap<String, (String, String)>
+}
+
+impl Registry {
+    pub fn empty() -> Registry {
+        Registry {globals: BTreeMap::new(), locals: BTreeMap::new(), pollinators: BTreeMap::new()}
+    }
+
+    /*
+    *   The operators of this crate and the pollinators CM, CMB, CA and CMW
+    */
+    pub fn standard() -> Registry {
+        let mut registry = Registry::empty();
+        registry.register_global("levy_pop1", || Box::<LevyPop1>::default());
+        registry.register_global("aligned_levy", || Box::<AlignedLevy>::default());
+        registry.register_global("gpx", || Box::<Gpx>::default());
+        registry.register_global("multi_gpx", || Box::new(MultiGpx::standard()));
+        registry.register_local("levy_circ", || with_scratch(levy_circ));
+        registry.register_local("change_worst", || with_scratch(change_worst));
+        registry.register_local("change_one", || with_scratch(change_one));
+        registry.register_local("try_change_critical", || with_scratch(try_change_critical));
+        registry.register_local("change_multiple", || with_scratch(change_multiple));
+        registry.register_local("change_multiple_best", || with_scratch(change_multiple_best));
+        registry.register_local("change_multiple_worst", || with_scratch(change_multiple_worst));
+        registry.register_local("change_all_critical", || with_scratch(change_all_critical));
+        registry.register_local("change_all_critical_opt", || with_scratch(change_all_critical_opt));
+        registry.register_local("tabucol", || Box::new(Tabucol::standard()));
+
+        registry.register_pollinator("CM", "levy_pop1", "change_multiple");
+        registry.register_pollinator("CMB", "levy_pop1", "change_multiple_best");
+        registry.register_pollinator("CA", "levy_pop1", "change_all_critical_opt");
+        registry.register_pollinator("CMW", "levy_pop1", "change_multiple_worst");
+        registry
+    }
+
+    pub fn register_global<F>(&mut self, name: &str, factory: F) where F: Fn() -> Box<dyn GlobalOperator> + Send + Sync + 'static {
+        self.globals.insert(name.to_string(), Arc::new(factory));
+    }
+
+    pub fn register_local<F>(&mut self, name: &str, factory: F) where F: Fn() -> Box<dyn LocalOperator> + Send + Sync + 'static {
+        self.locals.insert(name.to_string(), Arc::new(factory));
+    }
+
+    /*
+    *   Register a name for a combination of a global and a local operator
+    */
+    pub fn register_pollinator(&mut self, name: &str, global: &str, local: &str) {
+        self.pollinators.insert(name.to_string(), (global.to_string(), local.to_string()));
+    }
+
+    pub fn global(&self, name: &str) -> Result<GlobalFactory, String> {
+        match self.globals.get(name) {
+            Some(factory) => Ok(factory.clone()),
+            None => Err(format!("Unknown global operator {}, use {}", name, self.globals.keys().cloned().collect::<Vec<String>>().join(", ")))
+        }
+    }
+
+    /*
+    *   A registered local operator, or several separated by slashes combined by adaptive operator selection
+    */
+    pub fn local(&self, name: &str) -> Result<LocalFactory, String> {
+        if name.contains('/') {
+            let factories = name.split('/').map(|part| self.local(part)).collect::<Result<Vec<LocalFactory>, String>>()?;
+            return Ok(Arc::new(move || Box::new(AdaptiveLocal::new(factories.iter().map(|factory| factory()).collect()))));
+        }
+        match self.locals.get(name) {
+            Some(factory) => Ok(factory.clone()),
+            None => Err(format!("Unknown local operator {}, use {}", name, self.locals.keys().cloned().collect::<Vec<String>>().join(", ")))
+        }
+    }
+
+    pub fn pollinator(&self, name: &str) -> Result<Pollinator, String> {
+        let (global, local) = match name.split_once('+') {
+            Some((global, local)) => (global, local),
+            None => match self.pollinators.get(name) {
+                Some((global, local)) => (global.as_str(), local.as_str()),
+                None => return Err(format!("Unknown pollinator {}, use {} or <global>+<local>", name, self.pollinators.keys().cloned().collect::<Vec<String>>().join(", ")))
+            }
+        };
+        Ok(Pollinator { name: name.to_string(), global: self.global(global)?, local: self.local(local)? })
+    }
+
+    pub fn global_names(&self) -> Vec<&str> {
+        self.globals.keys().map(|name| name.as_str()).collect()
+    }
+
+    pub fn local_names(&self) -> Vec<&str> {
+        self.locals.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/*
+*   The registry used by the solvers and the command line, other crates can add their operators to it
+*/
+pub fn registry() -> &'static RwLock<Registry> {
+    static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Registry::standard()))
+}
+
+pub fn register_global<F>(name: &str, factory: F) where F: Fn() -> Box<dyn GlobalOperator> + Send + Sync + 'static {
+    registry().write().unwrap().register_global(name, factory);
+}
+
+pub fn register_local<F>(name: &str, factory: F) where F: Fn() -> Box<dyn LocalOperator> + Send + Sync + 'static {
+    registry().write().unwrap().register_local(name, factory);
+}
+
+pub fn register_pollinator(name: &str, global: &str, local: &str) {
+    registry().write().unwrap().register_pollinator(name, global, local);
+}
@@ -0,0 +1,156 @@
+use super::structs::*;
+use super::greedy::*;
+use super::registry::*;
+use super::crossover::*;
+use super::levy::*;
+
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+use rand::Rng;
+use rand::seq::index;
+use serde::{Serialize, Deserialize};
+
+/*
+*   Share of the vertices a perturbation restart gives a random color
+*/
+pub const PERTURBATION: f64 = 0.1;
+
+/*
+*   Number of best flowers a crossover restart chooses its parents from
+*/
+pub const ELITES: usize = 4;
+
+/*
+*   How a flower is replaced when it reaches its lifetime limit or the population is restarted
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Restart {
+    // A random coloring
+    #[default]
+    Random,
+    // Random colors for a share of the vertices of the flower
+    Perturb,
+    // DSATUR with k colors and random tie-breaking
+    Dsatur,
+    // Greedy coloring with k colors in a random vertex order
+    Greedy,
+    // GPX of two random flowers among the best flowers
+    Crossover
+}
+
+impl Restart {
+    pub const ALL: [Restart; 5] = [Restart::Random, Restart::Perturb, Restart::Dsatur, Restart::Greedy, Restart::Crossover];
+
+    pub fn is_random(&self) -> bool {
+        *self == Restart::Random
+    }
+
+    /*
+    *   The number of best flowers the strategy needs
+    */
+    pub fn parents(&self) -> usize {
+        match self {
+            Restart::Crossover => ELITES,
+            _ => 1
+        }
+    }
+
+    /*
+    *   Replace x by a new coloring with k colors, keeping its index. Parents are the best flowers of
+    *   the population, best first. A crossover with fewer than two other parents restarts at random.
+    *   Returns the strategy that was applied.
+    */
+    pub fn apply(&self, rng: &mut Rand, g: &Graph, x: &mut Coloring, parents: &[Coloring], k: usize, levy: &LevyFlight) -> Restart {
+        let elites: Vec<&Coloring> = parents.iter().filter(|parent| parent.index != x.index).collect();
+        let applied = match self {
+            Restart::Perturb => {
+                let n = x.len();
+                for i in index::sample(rng, n, ((n as f64 * PERTURBATION) as usize).clamp(1, n)) {
+                    x.recolor(i, rng.gen_range(1..=k), g);
+                }
+                Restart::Perturb
+            }
+            Restart::Dsatur => {
+                *x = Coloring::from_vec(x.index, random_dsatur(g, k, rng), g);
+                Restart::Dsatur
+            }
+            Restart::Greedy => {
+                *x = Coloring::from_vec(x.index, random_greedy(g, k, rng), g);
+                Restart::Greedy
+            }
+            Restart::Crossover if elites.len() >= 2 => {
+                let a = rng.gen_range(0..elites.len());
+                let b = (a + rng.gen_range(1..elites.len())) % elites.len();
+                let mut offspring = elites[a].clone();
+                offspring.index = x.index;
+                Gpx::default().apply(rng, g, std::slice::from_ref(elites[b]), elites[a], &mut offspring, k, levy);
+                *x = offspring;
+                Restart::Crossover
+            }
+            Restart::Random | Restart::Crossover => {
+                *x = Coloring::new(x.index, g, k, rng);
+                Restart::Random
+            }
+        };
+        x.lifetime = 0;
+        applied
+    }
+}
+
+/*
+*   The number of flowers restarted with every strategy, in the order of Restart::ALL
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RestartCounts(pub [usize; Restart::ALL.len()]);
+
+impl RestartCounts {
+    pub fn of(strategy: Option<Restart>) -> RestartCounts {
+        let mut counts = RestartCounts::default();
+        if let Some(strategy) = strategy {
+            counts.0[strategy as usize] += 1;
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+impl Add for RestartCounts {
+    type Output = RestartCounts;
+
+    fn add(self, other: RestartCounts) -> RestartCounts {
+        RestartCounts(std::array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+}
+
+impl FromStr for Restart {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Restart, String> {
+        match s {
+            "random" => Ok(Restart::Random),
+            "perturb" => Ok(Restart::Perturb),
+            "dsatur" => Ok(Restart::Dsatur),
+            "greedy" => Ok(Restart::Greedy),
+            "crossover" => Ok(Restart::Crossover),
+            _ => Err(format!("Unknown restart strategy {}, use random, perturb, dsatur, greedy or crossover", s))
+        }
+    }
+}
+
+impl fmt::Display for Restart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Restart::Random => "random",
+            Restart::Perturb => "perturb",
+            Restart::Dsatur => "dsatur",
+            Restart::Greedy => "greedy",
+            Restart::Crossover => "crossover"
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,310 @@
+use std::f64::consts::PI;
+
+/*
+*   Natural logarithm of the gamma function (Lanczos approximation)
+*/
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5];
+    if x < 0.5 {
+        // Reflection formula
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let mut series = 1.000000000190015;
+    for (j, c) in COEFFICIENTS.iter().enumerate() {
+        series += c / (x + 1.0 + j as f64);
+    }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/*
+*   Gamma function
+*/
+pub fn gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        PI / ((PI * x).sin() * gamma(1.0 - x))
+    } else {
+        ln_gamma(x).exp()
+    }
+}
+
+/*
+*   Regularized upper incomplete gamma function Q(a, x)
+*/
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let ln_prefix = -x + a * x.ln() - ln_gamma(a);
+    if x < a + 1.0 {
+        // Series representation of P(a, x)
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut ap = a;
+        for _ in 0..1000 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        1.0 - sum * ln_prefix.exp()
+    } else {
+        // Continued fraction representation of Q(a, x) (modified Lentz)
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        ln_prefix.exp() * h
+    }
+}
+
+/*
+*   Survival function of the chi-squared distribution with df degrees of freedom
+*/
+pub fn chi_squared_sf(x: f64, df: f64) -> f64 {
+    gamma_q(df / 2.0, x / 2.0)
+}
+
+/*
+*   Complementary error function (Numerical Recipes erfcc, relative error below 1.2e-7)
+*/
+pub fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let ans = t * (-z * z - 1.26551223 + t * (1.00002368 + t * (0.37409196 + t * (0.09678418
+        + t * (-0.18628806 + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587
+        + t * (-0.82215223 + t * 0.17087277))))))))).exp();
+    if x >= 0.0 { ans } else { 2.0 - ans }
+}
+
+/*
+*   Cumulative distribution function of the standard normal distribution
+*/
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+/*
+*   Ranks of the values (1 = smallest), tied values get the average of their ranks
+*/
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for &index in &order[i..=j] {
+            ranks[index] = rank;
+        }
+        i = j + 1;
+    }
+    ranks
+}
+
+pub struct MannWhitney {
+    pub u: f64,
+    pub z: f64,
+    pub p_value: f64
+}
+
+/*
+*   Two-sided Wilcoxon rank-sum / Mann-Whitney U test, using the normal approximation
+*   with tie and continuity correction
+*/
+pub fn mann_whitney(a: &[f64], b: &[f64]) -> MannWhitney {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let n = n1 + n2;
+    let all: Vec<f64> = a.iter().chain(b.iter()).copied().collect();
+    let r = ranks(&all);
+    let rank_sum: f64 = r[..a.len()].iter().sum();
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+
+    let mut sorted = all.clone();
+    sorted.sort_by(|x, y| x.total_cmp(y));
+    let mut ties = 0.0;
+    let mut i = 0;
+    while i < sorted.len() {
+        let j = sorted[i..].iter().take_while(|&&x| x == sorted[i]).count();
+        ties += (j * j * j - j) as f64;
+        i += j;
+    }
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return MannWhitney {u, z: 0.0, p_value: 1.0};
+    }
+    let diff = u - mean;
+    let z = (diff.abs() - 0.5).max(0.0) * diff.signum() / variance.sqrt();
+    MannWhitney {u, z, p_value: (2.0 * (1.0 - normal_cdf(z.abs()))).min(1.0)}
+}
+
+pub struct Friedman {
+    pub average_ranks: Vec<f64>,
+    pub statistic: f64,
+    pub p_value: f64
+}
+
+/*
+*   Friedman test over blocks (rows) of measurements for every treatment (columns), lower is better
+*/
+pub fn friedman(blocks: &[Vec<f64>]) -> Friedman {
+    let treatments = blocks.first().map_or(0, |block| block.len());
+    let n = blocks.len() as f64;
+    let k = treatments as f64;
+    let mut average_ranks = vec![0.0; treatments];
+    let mut ties = 0.0;
+    for block in blocks {
+        for (total, rank) in average_ranks.iter_mut().zip(ranks(block)) {
+            *total += rank / n;
+        }
+        let mut sorted = block.clone();
+        sorted.sort_by(|x, y| x.total_cmp(y));
+        let mut i = 0;
+        while i < sorted.len() {
+            let j = sorted[i..].iter().take_while(|&&x| x == sorted[i]).count();
+            ties += (j * j * j - j) as f64;
+            i += j;
+        }
+    }
+    if treatments < 2 || blocks.is_empty() {
+        return Friedman {average_ranks, statistic: 0.0, p_value: 1.0};
+    }
+    let sum_squares: f64 = average_ranks.iter().map(|r| r * r).sum();
+    let correction = 1.0 - ties / (n * (k * k * k - k));
+    let statistic = if correction > 0.0 {
+        12.0 * n / (k * (k + 1.0)) * (sum_squares - k * (k + 1.0) * (k + 1.0) / 4.0) / correction
+    } else {
+        0.0
+    };
+    Friedman {average_ranks, statistic, p_value: chi_squared_sf(statistic, k - 1.0)}
+}
+
+/*
+*   Critical difference of average ranks for the Nemenyi post-hoc test at alpha = 0.05
+*   (Demsar 2006), None for more than 10 treatments
+*/
+pub fn nemenyi_critical_difference(treatments: usize, blocks: usize) -> Option<f64> {
+    const Q_05: [f64; 9] = [1.960, 2.343, 2.569, 2.728, 2.850, 2.949, 3.031, 3.102, 3.164];
+    let q = *Q_05.get(treatments.checked_sub(2)?)?;
+    let k = treatments as f64;
+    Some(q * (k * (k + 1.0) / (6.0 * blocks as f64)).sqrt())
+}
+
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/*
+*   Regularized incomplete beta function I_x(a, b)
+*/
+pub fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    } else if x >= 1.0 {
+        return 1.0;
+    }
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        ln_front.exp() * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - ln_front.exp() * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/*
+*   Continued fraction for the incomplete beta function (modified Lentz)
+*/
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    let tiny = 1e-300;
+    let clamp = |v: f64| if v.abs() < tiny { tiny } else { v };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..1000 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a - 1.0 + 2.0 * m) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        h *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 1.0 + 2.0 * m));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    h
+}
+
+/*
+*   Cumulative distribution function of Student's t distribution
+*/
+pub fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t > 0.0 { 1.0 - tail } else { tail }
+}
+
+/*
+*   Quantile of Student's t distribution, found by bisection
+*/
+pub fn student_t_quantile(p: f64, df: f64) -> f64 {
+    let (mut low, mut high) = (-1e4, 1e4);
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if student_t_cdf(mid, df) < p {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/*
+*   Critical difference of rank sums for the Conover post-hoc test after a Friedman test
+*   (as used by F-Race and irace). Two treatments differ when their rank sums differ by more.
+*/
+pub fn conover_critical_difference(blocks: &[Vec<f64>], alpha: f64) -> f64 {
+    let n = blocks.len() as f64;
+    let k = blocks.first().map_or(0, |block| block.len()) as f64;
+    let mut rank_sums = vec![0.0; k as usize];
+    let mut sum_squares = 0.0;
+    for block in blocks {
+        for (total, rank) in rank_sums.iter_mut().zip(ranks(block)) {
+            *total += rank;
+            sum_squares += rank * rank;
+        }
+    }
+    let df = (n - 1.0) * (k - 1.0);
+    let variance = 2.0 * (n * sum_squares - rank_sums.iter().map(|r| r * r).sum::<f64>()) / df;
+    student_t_quantile(1.0 - alpha / 2.0, df) * variance.max(0.0).sqrt()
+}
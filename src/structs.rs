@@ -1,34 +1,222 @@
 use std::ops::{Index, IndexMut};
+use std::fmt;
 use std::fs;
-use std::collections::{HashSet, HashMap};
-use rand::{Rng, thread_rng};
+use std::io::Write;
+use std::collections::{HashSet, BTreeSet, BTreeMap};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use rand::{Rng, SeedableRng};
 use rand::distributions::Uniform;
 
-use rand::prelude::ThreadRng;
+use rand::rngs::StdRng;
 use itertools::Itertools;
+use serde::{Serialize, Deserialize};
+use crate::control::Control;
+use crate::restart::{Restart, RestartCounts};
+use crate::archive::ARCHIVE_SIZE;
 
 //---------------------------------------------------------------------------------------//
 
-#[derive(Clone, Copy)]
+pub type Rand = StdRng;
+
+/*
+*   Stream index reserved for drawing whole populations
+*/
+pub const POPULATE_STREAM: usize = usize::MAX;
+
+/*
+*   Independent random number stream of a seeded run for one flower (index) in one generation.
+*   Every random decision of a run is drawn from such a stream, which makes runs reproducible
+*   no matter how rayon schedules the flowers.
+*/
+pub fn rng_stream(seed: u64, generation: usize, index: usize) -> Rand {
+    // SplitMix64 finalizer over the combined inputs
+    let mut z = seed;
+    for value in [generation as u64, index as u64] {
+        z = z.wrapping_add(0x9E3779B97F4A7C15).wrapping_add(value);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^= z >> 31;
+    }
+    Rand::seed_from_u64(z)
+}
+
+//---------------------------------------------------------------------------------------//
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Parameters {
     pub lambda: f32,
+    // Stability index in (0, 2] of Lévy flights by Mantegna's algorithm, none for the
+    // Lévy distribution c / N^2 of the original DFPA
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beta: Option<f32>,
     pub switch_p: f64,
-    pub lifetime_limit: usize
+    pub lifetime_limit: usize,
+    pub pop_size: usize,
+    pub min_distance: usize,
+    pub track_diversity: bool,
+    #[serde(skip_serializing_if = "Control::is_fixed")]
+    pub control: Control,
+    // How flowers that reach the lifetime limit are replaced
+    #[serde(skip_serializing_if = "Restart::is_random")]
+    pub restart: Restart,
+    // Generations without a better flower after which all flowers but the best are restarted, 0 never
+    #[serde(skip_serializing_if = "is_zero")]
+    pub stagnation_limit: usize,
+    // Distinct legal colorings the archive keeps for every k
+    #[serde(skip_serializing_if = "is_archive_size")]
+    pub archive_size: usize,
+    // Replace the flowers of a population restart by the archived colorings of k + 1 with a color class dropped
+    #[serde(skip_serializing_if = "is_false")]
+    pub reinject: bool
 }
 
 impl Parameters {
     pub fn standard() -> Parameters {
         Parameters {
             lambda: 1.5,
+            beta: None,
             switch_p: 0.2,
-            lifetime_limit: 30
+            lifetime_limit: 30,
+            pop_size: crate::POP_SIZE,
+            min_distance: 0,
+            track_diversity: false,
+            control: Control::Fixed,
+            restart: Restart::Random,
+            stagnation_limit: 0,
+            archive_size: ARCHIVE_SIZE,
+            reinject: false
+        }
+    }
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+fn is_archive_size(value: &usize) -> bool {
+    *value == ARCHIVE_SIZE
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl Default for Parameters {
+    fn default() -> Parameters {
+        Parameters::standard()
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "lambda={} switch_p={} lifetime_limit={}", self.lambda, self.switch_p, self.lifetime_limit)?;
+        if let Some(beta) = self.beta {
+            write!(f, " beta={}", beta)?;
+        }
+        if self.pop_size != crate::POP_SIZE {
+            write!(f, " pop_size={}", self.pop_size)?;
+        }
+        if self.min_distance > 0 {
+            write!(f, " min_distance={}", self.min_distance)?;
+        }
+        if !self.control.is_fixed() {
+            write!(f, " control={}", self.control)?;
+        }
+        if !self.restart.is_random() {
+            write!(f, " restart={}", self.restart)?;
+        }
+        if self.stagnation_limit > 0 {
+            write!(f, " stagnation_limit={}", self.stagnation_limit)?;
+        }
+        if self.archive_size != ARCHIVE_SIZE {
+            write!(f, " archive_size={}", self.archive_size)?;
+        }
+        if self.reinject {
+            write!(f, " reinject")?;
+        }
+        Ok(())
+    }
+}
+
+//---------------------------------------------------------------------------------------//
+
+/*
+*   Stopping criteria of a single run, the generation limit applies to every k
+*   and the time limit (in seconds) to the run as a whole
+*/
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Budget {
+    pub max_gen: usize,
+    pub time_limit: Option<f64>
+}
+
+impl Budget {
+    pub fn standard() -> Budget {
+        Budget {
+            max_gen: crate::MAX_GEN,
+            time_limit: None
+        }
+    }
+
+    /*
+    *   Whether the time limit is reached after elapsed seconds
+    */
+    pub fn is_exceeded(&self, elapsed: f64) -> bool {
+        self.time_limit.is_some_and(|limit| elapsed >= limit)
+    }
+}
+
+impl Default for Budget {
+    fn default() -> Budget {
+        Budget::standard()
+    }
+}
+
+//---------------------------------------------------------------------------------------//
+
+/*
+*   Outcome of a single run, times are in seconds
+*/
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunResult {
+    pub k: usize,
+    pub generations: usize,
+    pub evaluations: usize,
+    pub time_to_best: f64,
+    pub total_time: f64,
+    // Flowers restarted after reaching their lifetime limit
+    #[serde(default)]
+    pub restarts: usize,
+    // Restarts of the whole population after it stagnated
+    #[serde(default)]
+    pub population_restarts: usize,
+    // Flowers restarted with every strategy, by lifetime and population restarts. A crossover
+    // restart without enough elites is counted as the random restart it falls back to.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub restarts_by_strategy: BTreeMap<String, usize>
+}
+
+impl RunResult {
+    /*
+    *   Result of a run that has not improved on the initial number of colors yet
+    */
+    pub fn new(k: usize) -> RunResult {
+        RunResult {k, generations: 0, evaluations: 0, time_to_best: 0.0, total_time: 0.0, restarts: 0, population_restarts: 0, restarts_by_strategy: BTreeMap::new()}
+    }
+
+    pub fn count_restarts(&mut self, counts: RestartCounts) {
+        for (strategy, count) in Restart::ALL.iter().zip(counts.0).filter(|(_, count)| *count > 0) {
+            *self.restarts_by_strategy.entry(strategy.to_string()).or_default() += count;
         }
     }
 }
 
 //---------------------------------------------------------------------------------------//
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Coloring {
     pub index: usize,
     pub solution: Vec<usize>,
@@ -41,7 +229,7 @@ impl Coloring {
     /*
     *   Generate a new coloring with k colors
     */
-    pub fn new(index: usize, g: &Graph, k: usize, rng: &mut ThreadRng) -> Coloring {
+    pub fn new(index: usize, g: &Graph, k: usize, rng: &mut Rand) -> Coloring {
         let n = g.len();
         let generator = Uniform::new_inclusive(1, k);
         let solution: Vec<usize> = rng.sample_iter(generator).take(n).collect();
@@ -73,6 +261,10 @@ impl Coloring {
     pub fn len(&self) -> usize {
         self.solution.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.solution.is_empty()
+    }
     //   3
     // 3 - 3
     // Local pollination:
@@ -80,6 +272,29 @@ impl Coloring {
     // 2 - 6
     
 
+    /*
+    *   Give node i another color and update the conflicts incrementally, including the total, in O(degree).
+    *   This requires a full graph
+    */
+    pub fn recolor(&mut self, i: usize, color: usize, g: &Graph) {
+        let old = self.solution[i];
+        if old == color {
+            return;
+        }
+        self.solution[i] = color;
+        for &neighbor in &g[i] {
+            if self.solution[neighbor] == old {
+                self.conflicts[neighbor] -= 1;
+                self.conflicts[i] -= 1;
+                self.tot_conflicts -= 1;
+            } else if self.solution[neighbor] == color {
+                self.conflicts[neighbor] += 1;
+                self.conflicts[i] += 1;
+                self.tot_conflicts += 1;
+            }
+        }
+    }
+
     /*
     *   Efficiently update the conflicts when modified from a previous state.
     *   Also refreshes the total number of conflicts.
@@ -104,27 +319,46 @@ impl Coloring {
     *   Update conflicts based on multiple changes. This method also refreshes the colorings total number of conflicts
     *   This (probably) requires a full graph
     */
-    pub fn update_multiple_conflicts(&mut self, indices: &Vec<usize>, prev: &Coloring, g: &Graph) {
-        let mut counter: HashMap<usize, usize> = HashMap::new();
+    pub fn update_multiple_conflicts(&mut self, indices: &[usize], prev: &Coloring, g: &Graph) {
         for &i in indices {
-            let conflicts_i = counter.entry(i).or_default();
             for &neighbor in &g[i] {
                 if self[i] == self[neighbor] {
-                    *conflicts_i += 1;
                     if prev[i] != prev[neighbor] {
                         self.conflicts[neighbor] += 1;
                     }
-                } else if self[i] != self[neighbor] && prev[i] == prev[neighbor] {
+                } else if prev[i] == prev[neighbor] {
                     self.conflicts[neighbor] -= 1;
                 }
             }
         }
-        for (i, conflicts) in counter.into_iter() {
-            self.conflicts[i] = conflicts;
+        // The counts of modified nodes are off when modified nodes are neighbors, count them again
+        for &i in indices {
+            self.conflicts[i] = g[i].iter().filter(|&&neighbor| self[i] == self[neighbor]).count();
+        }
+        self.refresh_tot_conflicts();
+    }
+
+    /*
+    *   Count all conflicts again, after the solution was changed arbitrarily
+    */
+    pub fn recompute_conflicts(&mut self, g: &Graph) {
+        for i in 0..self.len() {
+            self.conflicts[i] = g[i].iter().filter(|&&neighbor| self[i] == self[neighbor]).count();
         }
         self.refresh_tot_conflicts();
     }
 
+    /*
+    *   Turn this coloring into a copy of other, reusing the allocated vectors
+    */
+    pub fn copy_from(&mut self, other: &Coloring) {
+        self.index = other.index;
+        self.solution.clone_from(&other.solution);
+        self.conflicts.clone_from(&other.conflicts);
+        self.tot_conflicts = other.tot_conflicts;
+        self.lifetime = other.lifetime;
+    }
+
     /*
     *   Re-calculate the colorings total number of conflicts
     */
@@ -136,10 +370,120 @@ impl Coloring {
     *   Calculate the number of used colors
     */
     pub fn get_k(&self) -> usize {
-        self.solution.iter().unique().count()
+        let mut used = vec![false; self.max_color() + 1];
+        let mut k = 0;
+        for &color in &self.solution {
+            if color > 0 && !used[color] {
+                used[color] = true;
+                k += 1;
+            }
+        }
+        k
+    }
+
+    fn max_color(&self) -> usize {
+        self.solution.iter().copied().max().unwrap_or(0)
+    }
+
+    /*
+    *   Apply rename[color] to every color, conflicts stay the same as long as rename is injective
+    */
+    fn rename(&mut self, rename: &[usize]) {
+        for color in self.solution.iter_mut() {
+            *color = rename[*color];
+        }
+    }
+
+    /*
+    *   Renumber the colors by the first vertex that uses them, so the first vertex gets color 1,
+    *   the next vertex with another color gets 2 and so on. Colorings that only differ by a permutation
+    *   of the colors are equal afterwards. Color 0 (no color) is kept. Returns the number of colors.
+    */
+    pub fn canonicalize(&mut self) -> usize {
+        let (rename, k) = self.canonical_rename();
+        self.rename(&rename);
+        k
+    }
+
+    fn canonical_rename(&self) -> (Vec<usize>, usize) {
+        let mut rename = vec![0; self.max_color() + 1];
+        let mut k = 0;
+        for &color in &self.solution {
+            if color > 0 && rename[color] == 0 {
+                k += 1;
+                rename[color] = k;
+            }
+        }
+        (rename, k)
+    }
+
+    /*
+    *   Renumber the colors to 1..=k keeping their order, after some colors became unused.
+    *   Returns k.
+    */
+    pub fn compact(&mut self) -> usize {
+        let mut rename = vec![0; self.max_color() + 1];
+        for &color in &self.solution {
+            rename[color] = 1;
+        }
+        let mut k = 0;
+        for new in rename.iter_mut().skip(1) {
+            if *new > 0 {
+                k += 1;
+                *new = k;
+            }
+        }
+        rename[0] = 0;
+        self.rename(&rename);
+        k
+    }
+
+    /*
+    *   The canonical form of the coloring, which can be compared and hashed
+    */
+    pub fn canonical(&self) -> CanonicalColoring {
+        let (rename, _) = self.canonical_rename();
+        CanonicalColoring(self.solution.iter().map(|&color| rename[color]).collect())
+    }
+
+    /*
+    *   True if both colorings put the vertices into the same color classes, whatever the colors are called
+    */
+    pub fn same_partition(&self, other: &Coloring) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        // The colors of both have to correspond one to one
+        let mut forward = vec![usize::MAX; self.max_color() + 1];
+        let mut backward = vec![usize::MAX; other.max_color() + 1];
+        for (&a, &b) in self.solution.iter().zip(&other.solution) {
+            if forward[a] == usize::MAX && backward[b] == usize::MAX {
+                forward[a] = b;
+                backward[b] = a;
+            } else if forward[a] != b || backward[b] != a {
+                return false;
+            }
+        }
+        true
+    }
+
+    /*
+    *   Hash of the canonical form, equal for colorings with the same partition
+    */
+    pub fn partition_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.canonical().hash(&mut hasher);
+        hasher.finish()
     }
 }
 
+/*
+*   The colors of a coloring numbered by first use (see Coloring::canonicalize), equal and hashed
+*   by content, e.g. to find duplicate flowers with a HashSet
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CanonicalColoring(pub Vec<usize>);
+
 impl Index<usize> for Coloring {
     type Output = usize;
     
@@ -155,6 +499,9 @@ impl IndexMut<usize> for Coloring {
     }
 }
 
+/*
+*   Flowers are compared by their index, use same_partition or canonical to compare colorings by content
+*/
 impl PartialEq for Coloring {
     fn eq(&self, other: &Self) -> bool {
         self.index == other.index
@@ -185,10 +532,114 @@ impl Graph {
         Graph {content: read_graph(file_name, true).unwrap()}
     }
 
+    /*
+    *   Build a full graph with n nodes from a list of (0-indexed) edges
+    */
+    pub fn from_edges(n: usize, edges: impl IntoIterator<Item = (usize, usize)>) -> Graph {
+        let mut graph = vec![HashSet::new(); n];
+        for (from, to) in edges {
+            if from != to {
+                graph[from].insert(to);
+                graph[to].insert(from);
+            }
+        }
+        Graph {content: graph.iter_mut().map(|set| set.drain().sorted_unstable().collect()).collect()}
+    }
+
+    /*
+    *   Write the graph to file in DIMACS format, every comment line is prefixed with "c "
+    */
+    pub fn write(&self, file_name: &str, comments: &[String]) -> std::io::Result<()> {
+        let edges: BTreeSet<(usize, usize)> = self.iter().enumerate()
+            .flat_map(|(from, neighbors)| neighbors.iter().map(move |&to| (from.min(to), from.max(to))))
+            .collect();
+        let mut file = std::io::BufWriter::new(fs::File::create(file_name)?);
+        for comment in comments {
+            writeln!(file, "c {}", comment)?;
+        }
+        writeln!(file, "p edge {} {}", self.len(), edges.len())?;
+        for (from, to) in edges {
+            writeln!(file, "e {} {}", from + 1, to + 1)?;
+        }
+        file.flush()
+    }
+
+    /*
+    *   Add a vertex without edges to a full graph, returns its index
+    */
+    pub fn add_vertex(&mut self) -> usize {
+        self.content.push(Vec::new());
+        self.content.len() - 1
+    }
+
+    /*
+    *   Remove vertex v and its edges from a full graph. The last vertex takes the index v,
+    *   its old index is returned unless v was the last vertex.
+    */
+    pub fn remove_vertex(&mut self, v: usize) -> Option<usize> {
+        for neighbor in std::mem::take(&mut self.content[v]) {
+            let position = self.content[neighbor].binary_search(&v).unwrap();
+            self.content[neighbor].remove(position);
+        }
+        let last = self.content.len() - 1;
+        self.content.swap_remove(v);
+        if v == last {
+            return None;
+        }
+        for i in 0..self.content[v].len() {
+            let neighbor = self.content[v][i];
+            let neighbors = &mut self.content[neighbor];
+            neighbors.remove(neighbors.binary_search(&last).unwrap());
+            let position = neighbors.binary_search(&v).unwrap_err();
+            neighbors.insert(position, v);
+        }
+        Some(last)
+    }
+
+    /*
+    *   Add the edge between a and b to a full graph, returns false if it already exists
+    */
+    pub fn add_edge(&mut self, a: usize, b: usize) -> bool {
+        if a == b {
+            return false;
+        }
+        let Err(position) = self.content[a].binary_search(&b) else {
+            return false;
+        };
+        self.content[a].insert(position, b);
+        let position = self.content[b].binary_search(&a).unwrap_err();
+        self.content[b].insert(position, a);
+        true
+    }
+
+    /*
+    *   Remove the edge between a and b from a full graph, returns false if there is none
+    */
+    pub fn remove_edge(&mut self, a: usize, b: usize) -> bool {
+        let Ok(position) = self.content[a].binary_search(&b) else {
+            return false;
+        };
+        self.content[a].remove(position);
+        let position = self.content[b].binary_search(&a).unwrap();
+        self.content[b].remove(position);
+        true
+    }
+
     pub fn len(&self) -> usize {
         self.content.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    /*
+    *   Calculate the number of edges of a full graph
+    */
+    pub fn num_edges(&self) -> usize {
+        self.iter().map(|vec| vec.len()).sum::<usize>() / 2
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, Vec<usize>> {
         self.content.iter()
     }
@@ -203,11 +654,10 @@ impl Graph {
     /*
     *   Initialize a population with random coloringsw
     */
-    pub fn populate(&self, n: usize, k: usize) -> Vec<Coloring> {
-        let mut rng = thread_rng();
+    pub fn populate(&self, rng: &mut Rand, n: usize, k: usize) -> Vec<Coloring> {
         let mut pop = Vec::with_capacity(n);
         for i in 0..n {
-            pop.push(Coloring::new(i, &self, k, &mut rng));
+            pop.push(Coloring::new(i, self, k, rng));
         }
         pop
     }
@@ -227,7 +677,7 @@ impl Index<usize> for Graph {
 fn read_graph(file_name: &str, simple: bool) -> Option<Vec<Vec<usize>>> {
     let content = fs::read_to_string(file_name).ok()?;
     let mut lines = content.split_terminator("\n")
-        .filter(|&line| line != "" && &line[0..1] != "c")
+        .filter(|&line| !line.is_empty() && &line[0..1] != "c")
         .map(|line| line.split_whitespace().collect::<Vec<&str>>());
 
     let header = lines.next()?;
@@ -249,5 +699,53 @@ fn read_graph(file_name: &str, simple: bool) -> Option<Vec<Vec<usize>>> {
         
     }
 
-    Some(graph.iter_mut().map(|set| set.drain().collect()).collect())
-}
\ No newline at end of file
+    Some(graph.iter_mut().map(|set| set.drain().sorted_unstable().collect()).collect())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(n: usize) -> Graph {
+        Graph::from_edges(n, (1..n).map(|i| (i - 1, i)))
+    }
+
+    #[test]
+    fn canonicalize_is_idempotent() {
+        let mut x = Coloring::from_vec(0, vec![3, 1, 3, 2, 0, 2], &path(6));
+        assert_eq!(x.canonicalize(), 3);
+        assert_eq!(x.solution, vec![1, 2, 1, 3, 0, 3]);
+        let once = x.solution.clone();
+        assert_eq!(x.canonicalize(), 3);
+        assert_eq!(x.solution, once);
+    }
+
+    #[test]
+    fn permuted_colorings_have_the_same_canonical_form() {
+        let g = path(7);
+        let x = Coloring::from_vec(0, vec![2, 4, 2, 1, 1, 4, 2], &g);
+        // 1 -> 3, 2 -> 4, 4 -> 1
+        let permuted = Coloring::from_vec(1, vec![4, 1, 4, 3, 3, 1, 4], &g);
+        assert_eq!(x.canonical(), permuted.canonical());
+        assert_eq!(x.partition_hash(), permuted.partition_hash());
+        assert!(x.same_partition(&permuted) && permuted.same_partition(&x));
+
+        // Merging two classes gives another partition
+        let merged = Coloring::from_vec(2, vec![2, 4, 2, 4, 4, 4, 2], &g);
+        assert_ne!(x.canonical(), merged.canonical());
+        assert!(!x.same_partition(&merged) && !merged.same_partition(&x));
+    }
+
+    #[test]
+    fn compact_keeps_conflicts() {
+        let g = Graph::from_edges(5, [(0, 1), (1, 2), (2, 3), (3, 4), (0, 4), (1, 3)]);
+        let mut x = Coloring::from_vec(0, vec![1, 5, 5, 9, 1], &g);
+        let (conflicts, tot_conflicts) = (x.conflicts.clone(), x.tot_conflicts);
+        assert_eq!(tot_conflicts, 2);
+        assert_eq!(x.compact(), 3);
+        assert_eq!(x.solution, vec![1, 2, 2, 3, 1]);
+        assert_eq!((x.conflicts.clone(), x.tot_conflicts), (conflicts, tot_conflicts));
+        x.recompute_conflicts(&g);
+        assert_eq!(x.tot_conflicts, tot_conflicts);
+        assert_eq!(x.get_k(), 3);
+    }
+}
@@ -0,0 +1,319 @@
+use super::bench::*;
+use super::statistics::*;
+use super::*;
+
+use std::fs;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use itertools::Itertools;
+use prettytable::*;
+use serde::{Serialize, Deserialize};
+
+/*
+*   The ranges the tuner samples configurations from
+*/
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParameterSpace {
+    pub lambda: (f32, f32),
+    // Without a range the configurations use the c / N^2 steps of the original DFPA
+    pub beta: Option<(f32, f32)>,
+    pub switch_p: (f64, f64),
+    pub lifetime_limit: (usize, usize),
+    pub pop_size: (usize, usize),
+    pub pollinators: Vec<String>
+}
+
+impl ParameterSpace {
+    pub fn standard() -> ParameterSpace {
+        ParameterSpace {
+            lambda: (0.5, 3.0),
+            beta: None,
+            switch_p: (0.05, 0.95),
+            lifetime_limit: (5, 100),
+            pop_size: (5, 50),
+            pollinators: ["CM", "CMB", "CMW", "CA"].iter().map(|p| p.to_string()).collect()
+        }
+    }
+
+    /*
+    *   Sample a configuration uniformly from the space
+    */
+    pub fn sample(&self, rng: &mut Rand) -> Configuration {
+        Configuration {
+            solver: format!("dfpa:{}", self.pollinators.choose(rng).expect("No pollinators to tune")),
+            parameters: Parameters {
+                lambda: rng.gen_range(self.lambda.0..=self.lambda.1),
+                beta: self.beta.map(|(low, high)| rng.gen_range(low..=high)),
+                switch_p: rng.gen_range(self.switch_p.0..=self.switch_p.1),
+                lifetime_limit: rng.gen_range(self.lifetime_limit.0..=self.lifetime_limit.1),
+                pop_size: rng.gen_range(self.pop_size.0..=self.pop_size.1),
+                ..Parameters::standard()
+            }
+        }
+    }
+
+    /*
+    *   Sample a configuration around a parent. The spread of the numeric parameters and the chance
+    *   of switching pollinator shrink with every iteration, so the search concentrates around the elites.
+    */
+    pub fn sample_near(&self, rng: &mut Rand, parent: &Configuration, iteration: usize) -> Configuration {
+        let spread = 0.5 * (iteration as f64).powf(-0.25);
+        let mut perturb = |value: f64, (low, high): (f64, f64)| {
+            let noise: f64 = rng.sample(StandardNormal);
+            (value + noise * spread * (high - low)).clamp(low, high)
+        };
+        let p = parent.parameters;
+        let lambda = perturb(p.lambda as f64, (self.lambda.0 as f64, self.lambda.1 as f64)) as f32;
+        let beta = p.beta.zip(self.beta).map(|(beta, (low, high))| perturb(beta as f64, (low as f64, high as f64)) as f32);
+        let switch_p = perturb(p.switch_p, self.switch_p);
+        let lifetime_limit = perturb(p.lifetime_limit as f64, (self.lifetime_limit.0 as f64, self.lifetime_limit.1 as f64)).round() as usize;
+        let pop_size = perturb(p.pop_size as f64, (self.pop_size.0 as f64, self.pop_size.1 as f64)).round() as usize;
+        let solver = if rng.gen_bool(1.0 / (iteration + 1) as f64) {
+            format!("dfpa:{}", self.pollinators.choose(rng).expect("No pollinators to tune"))
+        } else {
+            parent.solver.clone()
+        };
+        Configuration {
+            solver,
+            parameters: Parameters { lambda, beta, switch_p, lifetime_limit, pop_size, ..p }
+        }
+    }
+}
+
+impl Default for ParameterSpace {
+    fn default() -> ParameterSpace {
+        ParameterSpace::standard()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Configuration {
+    pub solver: String,
+    pub parameters: Parameters
+}
+
+/*
+*   The tuning budget, in solver runs or in seconds of wall-clock time spent in the solver. The runs
+*   are made one after another, but a run uses all cores, so it takes more CPU time than that.
+*/
+#[derive(Clone, Copy)]
+pub enum TuningBudget {
+    Runs(usize),
+    WallTime(f64)
+}
+
+pub struct RacingSettings {
+    pub budget: TuningBudget,
+    pub candidates: usize,
+    pub elites: usize,
+    pub first_test: usize,
+    pub max_blocks: usize,
+    pub alpha: f64,
+    pub seed: u64
+}
+
+/*
+*   A surviving configuration with its results over the blocks of its last race
+*/
+#[derive(Serialize)]
+pub struct Elite {
+    pub configuration: Configuration,
+    pub mean_rank: f64,
+    pub mean_k: f64,
+    pub runs: usize
+}
+
+/*
+*   A training suite with the space to tune over
+*/
+#[derive(Deserialize)]
+pub struct TuningSuite {
+    #[serde(flatten)]
+    pub suite: Suite,
+    #[serde(default)]
+    pub space: ParameterSpace
+}
+
+struct Spent {
+    runs: usize,
+    time: f64,
+    blocks: usize
+}
+
+impl Spent {
+    fn allows(&self, budget: TuningBudget, runs: usize) -> bool {
+        match budget {
+            TuningBudget::Runs(limit) => self.runs + runs <= limit,
+            TuningBudget::WallTime(limit) => self.time < limit
+        }
+    }
+}
+
+/*
+*   Iterated racing (irace-style). Every iteration samples new candidates around the elites of the previous
+*   one and races them: all surviving candidates are run on one more instance and seed (a block), and once
+*   first_test blocks are done, a Friedman test with Conover post-hoc comparisons eliminates the candidates
+*   that are significantly worse than the best. A race ends when at most elites candidates are left or after
+*   max_blocks blocks. The cost of a run is its number of colors, ties are broken by the time to reach it.
+*   Every run is recorded in <out_dir>/<name>_tuning.csv and <out_dir>/<name>_tuning.jsonl.
+*/
+pub fn race(suite: &Suite, space: &ParameterSpace, settings: &RacingSettings) -> Vec<Elite> {
+    let graphs: Vec<(&Instance, Graph, usize)> = suite.instances.iter().map(|instance| {
+        let graph = Graph::read(&format!("graphs/{}.col", instance.graph));
+        let (greedy, _) = dsatur2(&graph);
+        (instance, graph, greedy)
+    }).collect();
+    let name = format!("{}_tuning", suite.name);
+    let mut records = RecordWriter::open(&suite.out_dir, &name);
+    let mut rng = Rand::seed_from_u64(settings.seed);
+    let mut spent = Spent {runs: 0, time: 0.0, blocks: 0};
+    let mut elites: Vec<Elite> = Vec::new();
+
+    for iteration in 1.. {
+        let mut candidates: Vec<Configuration> = elites.iter().map(|elite| elite.configuration.clone()).collect();
+        while candidates.len() < settings.candidates.max(settings.elites + 1) {
+            candidates.push(if elites.is_empty() {
+                space.sample(&mut rng)
+            } else {
+                // Parents are chosen with a weight decreasing linearly with their rank
+                let weights: Vec<usize> = (1..=elites.len()).rev().collect();
+                let parent = (0..elites.len()).collect::<Vec<usize>>().choose_weighted(&mut rng, |&i| weights[i]).map(|&i| &elites[i].configuration).unwrap();
+                space.sample_near(&mut rng, parent, iteration)
+            });
+        }
+        if !spent.allows(settings.budget, candidates.len() * settings.first_test.max(1)) {
+            break;
+        }
+        println!("Iteration {}: racing {} candidates", iteration, candidates.len());
+
+        let mut order: Vec<usize> = (0..graphs.len()).collect();
+        order.shuffle(&mut rng);
+        let mut alive: Vec<usize> = (0..candidates.len()).collect();
+        let mut costs: Vec<Vec<f64>> = Vec::new();
+        let mut colors: Vec<Vec<usize>> = Vec::new();
+        while spent.allows(settings.budget, alive.len()) && costs.len() < settings.max_blocks
+            && (alive.len() > settings.elites || costs.len() < settings.first_test) {
+            let (instance, graph, greedy) = &graphs[order[costs.len() % order.len()]];
+            let seed = suite.seed + spent.blocks as u64;
+            let mut block_costs = vec![f64::NAN; candidates.len()];
+            let mut block_colors = vec![0; candidates.len()];
+            for &c in &alive {
+                let candidate = &candidates[c];
+                let result = run_solver(&candidate.solver, graph, greedy-1, candidate.parameters, Some(instance.k_star), suite, seed, &mut ());
+                records.write(&RunRecord {
+                    suite: name.clone(),
+                    instance: instance.graph.clone(),
+                    k_star: instance.k_star,
+                    solver: candidate.solver.clone(),
+                    parameters: candidate.parameters,
+                    seed,
+                    repetition: spent.blocks,
+                    config: Some(RunConfig::new(&candidate.solver, suite, Some(instance.k_star))),
+                    k_init: greedy-1,
+                    k_final: result.k,
+                    time_to_best: result.time_to_best,
+                    total_time: result.total_time,
+                    generations: result.generations,
+                    evaluations: result.evaluations,
+                    restarts: result.restarts,
+                    population_restarts: result.population_restarts,
+                    restarts_by_strategy: result.restarts_by_strategy.clone(),
+                    git_revision: String::from(env!("GIT_REVISION")),
+                    trajectory: Vec::new()
+                });
+                block_costs[c] = result.k as f64 + result.time_to_best / (1.0 + result.time_to_best);
+                block_colors[c] = result.k;
+                spent.runs += 1;
+                spent.time += result.total_time;
+            }
+            costs.push(block_costs);
+            colors.push(block_colors);
+            spent.blocks += 1;
+
+            if costs.len() >= settings.first_test && alive.len() > 1 {
+                let blocks: Vec<Vec<f64>> = costs.iter().map(|block| alive.iter().map(|&c| block[c]).collect()).collect();
+                if friedman(&blocks).p_value < settings.alpha {
+                    let critical = conover_critical_difference(&blocks, settings.alpha);
+                    let rank_sums = rank_sums(&blocks);
+                    let best = rank_sums.iter().copied().fold(f64::INFINITY, f64::min);
+                    let before = alive.len();
+                    alive = alive.iter().zip(&rank_sums).filter(|(_, &r)| r - best <= critical).map(|(&c, _)| c).collect();
+                    println!("\tBlock {}: eliminated {} candidates, {} alive", costs.len(), before - alive.len(), alive.len());
+                }
+            }
+        }
+        if costs.is_empty() {
+            break;
+        }
+
+        // The survivors are ordered by their mean rank over the blocks of this race
+        let blocks: Vec<Vec<f64>> = costs.iter().map(|block| alive.iter().map(|&c| block[c]).collect()).collect();
+        let mean_ranks: Vec<f64> = rank_sums(&blocks).iter().map(|r| r / blocks.len() as f64).collect();
+        elites = alive.iter().zip(mean_ranks)
+            .sorted_by(|a, b| a.1.total_cmp(&b.1))
+            .take(settings.elites)
+            .map(|(&c, mean_rank)| Elite {
+                configuration: candidates[c].clone(),
+                mean_rank,
+                mean_k: mean(&colors.iter().map(|block| block[c] as f64).collect::<Vec<f64>>()),
+                runs: colors.len()
+            })
+            .collect();
+        println!("\tBest: {} [{}], mean k = {:.2}", elites[0].configuration.solver, elites[0].configuration.parameters, elites[0].mean_k);
+    }
+    elites
+}
+
+fn rank_sums(blocks: &[Vec<f64>]) -> Vec<f64> {
+    let mut sums = vec![0.0; blocks.first().map_or(0, |block| block.len())];
+    for block in blocks {
+        for (total, rank) in sums.iter_mut().zip(ranks(block)) {
+            *total += rank;
+        }
+    }
+    sums
+}
+
+/*
+*   Tune DFPA on the instances of a training suite and write the elite configurations to <out_dir>/<name>_elites.json.
+*   The budget is a number of solver runs or the wall-clock seconds spent in the solver.
+*   Usage: tune [suite file] [--runs n | --wall-time seconds] [--candidates n] [--elites n] [--first-test n] [--max-blocks n] [--seed s]
+*/
+pub fn tune(args: &[String]) {
+    let file_name = positional_args(args).first().copied().unwrap_or("suites/tuning.json");
+    let content = fs::read_to_string(file_name).unwrap_or_else(|err| panic!("Could not read suite {}: {}", file_name, err));
+    let tuning: TuningSuite = serde_json::from_str(&content).unwrap_or_else(|err| panic!("Invalid suite {}: {}", file_name, err));
+
+    let budget = match (get_flag(args, "--runs"), get_flag(args, "--wall-time")) {
+        (_, Some(time)) => TuningBudget::WallTime(time.parse().expect("Invalid time budget")),
+        (Some(runs), None) => TuningBudget::Runs(runs.parse().expect("Invalid run budget")),
+        (None, None) => TuningBudget::Runs(500)
+    };
+    let parse = |flag: &str, default: usize| get_flag(args, flag).map_or(default, |value| value.parse().unwrap_or_else(|_| panic!("Invalid value for {}", flag)));
+    let settings = RacingSettings {
+        budget,
+        candidates: parse("--candidates", 12),
+        elites: parse("--elites", 3),
+        first_test: parse("--first-test", 5),
+        max_blocks: parse("--max-blocks", 20),
+        alpha: 0.05,
+        seed: get_flag(args, "--seed").map_or(tuning.suite.seed, |s| s.parse().expect("Invalid seed"))
+    };
+
+    let elites = race(&tuning.suite, &tuning.space, &settings);
+    if elites.is_empty() {
+        panic!("The budget does not allow a single race of {} candidates over {} blocks", settings.candidates.max(settings.elites + 1), settings.first_test.max(1));
+    }
+
+    let mut table = table!(["Rank", "Solver", "Parameters", "Mean rank", "Mean k", "Runs"]);
+    for (i, elite) in elites.iter().enumerate() {
+        table.add_row(row![i + 1, elite.configuration.solver, elite.configuration.parameters, format!("{:.2}", elite.mean_rank), format!("{:.2}", elite.mean_k), elite.runs]);
+    }
+    println!("Elite configurations:");
+    table.printstd();
+
+    let path = format!("{}/{}_elites.json", tuning.suite.out_dir, tuning.suite.name);
+    fs::write(&path, serde_json::to_string_pretty(&elites).unwrap()).unwrap_or_else(|err| panic!("Could not write {}: {}", path, err));
+    println!("Elites written to {}", path);
+}
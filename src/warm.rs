@@ -0,0 +1,102 @@
+use super::structs::*;
+use super::archive::*;
+use super::partial::*;
+use super::restart::*;
+
+use std::fs;
+use std::collections::HashSet;
+use rand::Rng;
+use rand::seq::index;
+
+/*
+*   Read the coloring to start from for a graph with n vertices. The file is either an archive written with
+*   --archive, of which the legal coloring with the fewest colors is used (or else the one with the fewest
+*   conflicts), or the colors 1..=k of the vertices in order, separated by whitespace. Vertices beyond the
+*   end of the file or with color 0 are uncolored, colors of vertices beyond n are ignored.
+*/
+pub fn read_solution(file_name: &str, n: usize) -> Vec<usize> {
+    let content = fs::read_to_string(file_name).unwrap_or_else(|err| panic!("Could not read solution {}: {}", file_name, err));
+    let mut solution: Vec<usize> = if content.trim_start().starts_with('[') {
+        let colorings: Vec<ArchivedColoring> = serde_json::from_str(&content).unwrap_or_else(|err| panic!("Invalid solution {}: {}", file_name, err));
+        colorings.into_iter()
+            .min_by_key(|coloring| (coloring.conflicts, coloring.k))
+            .unwrap_or_else(|| panic!("No coloring in {}", file_name))
+            .solution
+    } else {
+        content.split_whitespace().map(|color| color.parse().unwrap_or_else(|_| panic!("Invalid color {} in {}", color, file_name))).collect()
+    };
+    solution.resize(n, 0);
+    solution
+}
+
+/*
+*   Turn a possibly partial coloring into a legal one by greedy completion. A vertex keeps its color
+*   unless a neighbor before it already has that color (the graph may have changed since the coloring
+*   was found), the other vertices get the smallest color none of their neighbors use. The colors are
+*   renumbered to 1..=k. Returns the coloring and the number of vertices that had to be colored.
+*/
+pub fn complete(g: &Graph, solution: &[usize]) -> (Coloring, usize) {
+    let mut colors = vec![0; g.len()];
+    for (i, &color) in solution.iter().enumerate() {
+        if color > 0 && g[i].iter().all(|&neighbor| colors[neighbor] != color) {
+            colors[i] = color;
+        }
+    }
+    let missing: Vec<usize> = (0..g.len()).filter(|&i| colors[i] == 0).collect();
+    for &i in &missing {
+        let neighbor_colors: HashSet<usize> = g[i].iter().map(|&neighbor| colors[neighbor]).collect();
+        colors[i] = (1..).find(|color| !neighbor_colors.contains(color)).unwrap();
+    }
+    let mut x = Coloring::from_vec(0, colors, g);
+    x.canonicalize();
+    (x, missing.len())
+}
+
+/*
+*   A coloring with k colors from a legal coloring x with colors 1..=k + 1: the smallest color class is
+*   uncolored and its vertices get the color 1..=k used by the fewest of their neighbors
+*/
+pub fn drop_smallest_class(g: &Graph, x: &Coloring, k: usize) -> Coloring {
+    let mut sizes = vec![0; k + 2];
+    for &color in &x.solution {
+        sizes[color] += 1;
+    }
+    let smallest = (1..=k + 1).min_by_key(|&color| sizes[color]).unwrap();
+    let mut partial = PartialColoring::empty(x.index, x.len());
+    for (i, &color) in x.solution.iter().enumerate() {
+        if color != smallest {
+            // The last color takes the place of the smallest class, so the others keep 1..=k
+            partial.color(i, if color == k + 1 { smallest } else { color });
+        }
+    }
+    partial.to_coloring(g, k)
+}
+
+/*
+*   A population of n flowers with k colors for a legal coloring x with k + 1 colors. The first count flowers
+*   start from x with its smallest color class dropped (see drop_smallest_class). Seeded flowers after the first
+*   also give a share PERTURBATION of the vertices a random color, so they do not all start alike. The other
+*   flowers are random colorings.
+*/
+pub fn warm_population(g: &Graph, x: &Coloring, count: usize, n: usize, k: usize, seed: u64) -> Vec<Coloring> {
+    let mut rng = rng_stream(seed, 0, POPULATE_STREAM);
+    let reduced = drop_smallest_class(g, x, k);
+
+    let mut pop = Vec::with_capacity(n);
+    let count = count.min(n);
+    for index in 0..count {
+        let mut flower = reduced.clone();
+        flower.index = index;
+        if index > 0 {
+            let vertices = x.len();
+            for i in index::sample(&mut rng, vertices, ((vertices as f64 * PERTURBATION) as usize).clamp(1, vertices)) {
+                flower.recolor(i, rng.gen_range(1..=k), g);
+            }
+        }
+        pop.push(flower);
+    }
+    for index in count..n {
+        pop.push(Coloring::new(index, g, k, &mut rng));
+    }
+    pop
+}